@@ -0,0 +1,39 @@
+//! Lists every `inputs/NN.txt` for the `embed-inputs` feature, so a new day's input is
+//! compiled in without keeping a second list of days.
+
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-changed=inputs");
+
+    let mut inputs = vec![];
+    if std::env::var_os("CARGO_FEATURE_EMBED_INPUTS").is_some() {
+        let dir = Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("inputs");
+        let entries = std::fs::read_dir(&dir)
+            .unwrap_or_else(|error| panic!("embed-inputs needs {}: {}", dir.display(), error));
+        for entry in entries {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|extension| extension != "txt") {
+                continue;
+            }
+            let day = path
+                .file_stem()
+                .and_then(|stem| stem.to_str()?.parse::<usize>().ok());
+            if let Some(day) = day {
+                inputs.push((day, path.to_str().unwrap().to_string()));
+            }
+        }
+    }
+    inputs.sort();
+
+    let entries = inputs
+        .iter()
+        .map(|(day, path)| format!("    ({}, include_str!({:?})),\n", day, path))
+        .collect::<String>();
+    let out = Path::new(&std::env::var("OUT_DIR").unwrap()).join("inputs.rs");
+    std::fs::write(
+        out,
+        format!("const INPUTS: &[(usize, &str)] = &[\n{}];\n", entries),
+    )
+    .unwrap();
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+use crate::parse::{self, ParseError};
+use crate::{Runner, Solution};
+
+/// Recorded answers of one person, keyed by day. `None` marks a part without a known answer.
+type Answers = HashMap<usize, [Option<i64>; 2]>;
+
+/// Solves every day for every person with inputs at `<dir>/<name>/NN.txt` and checks the
+/// results against `<dir>/<name>/answers.txt`. Returns false if any day failed, was wrong, or
+/// is missing its input or recorded answers, and when there is nobody to check.
+pub fn run(dir: &str, days: &[Runner]) -> bool {
+    let mut people = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>(),
+        Err(error) => {
+            eprintln!("Could not read {}: {}", dir, error);
+            return false;
+        }
+    };
+    people.sort();
+    if people.is_empty() {
+        eprintln!("No inputs to check, {} has no directory per person", dir);
+        return false;
+    }
+
+    // Panics are reported in the table instead of on stderr
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    println!(
+        "{:<12} {:>3} {:>16} {:>16} {:>12}  status",
+        "name", "day", "part 1", "part 2", "time"
+    );
+    let mut failures = 0;
+    for person in &people {
+        let path = Path::new(dir).join(person);
+        let answers = match std::fs::read_to_string(path.join("answers.txt")) {
+            Ok(input) => parse_answers(&input),
+            Err(error) => {
+                eprintln!("Could not read answers for {}: {}", person, error);
+                failures += 1;
+                continue;
+            }
+        };
+        let answers = match answers {
+            Ok(answers) => answers,
+            Err(error) => {
+                eprintln!("Failed to parse answers for {}", person);
+                eprint!("{}", error);
+                failures += 1;
+                continue;
+            }
+        };
+
+        for runner in days {
+            let Ok(input) = std::fs::read(path.join(format!("{:02}.txt", runner.day))) else {
+                failures += 1;
+                println!(
+                    "{:<12} {:>3} {:>16} {:>16} {:>12}  missing input",
+                    person, runner.day, "", "", ""
+                );
+                continue;
+            };
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| (runner.solve)(&input)));
+            let (part_1, part_2, time, status) = match result {
+                Ok(Ok(solution)) => {
+                    let status = check(&solution, answers.get(&runner.day));
+                    (
+                        solution.part_1.to_string(),
+                        solution.part_2.to_string(),
+                        format!("{:.3?}", solution.total_time()),
+                        status,
+                    )
+                }
+                Ok(Err(error)) => {
+                    let status = format!("parse error at line {}", error.line());
+                    (String::new(), String::new(), String::new(), Err(status))
+                }
+                Err(payload) => {
+                    let message = payload
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    let status = format!("panicked: {}", message);
+                    (String::new(), String::new(), String::new(), Err(status))
+                }
+            };
+
+            let status = status.unwrap_or_else(|status| {
+                failures += 1;
+                status
+            });
+            println!(
+                "{:<12} {:>3} {:>16} {:>16} {:>12}  {}",
+                person, runner.day, part_1, part_2, time, status
+            );
+            crate::print_memo_stats();
+        }
+    }
+
+    panic::set_hook(hook);
+    println!("{} people, {} failures", people.len(), failures);
+    failures == 0
+}
+
+fn check(solution: &Solution, answers: Option<&[Option<i64>; 2]>) -> Result<String, String> {
+    let Some(answers) = answers else {
+        return Err("no recorded answers".to_string());
+    };
+
+    let wrong = [solution.part_1, solution.part_2]
+        .iter()
+        .zip(answers)
+        .enumerate()
+        .filter_map(|(idx, (actual, expected))| match expected {
+            Some(expected) if expected != actual => {
+                Some(format!("part {} expected {}", idx + 1, expected))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if wrong.is_empty() {
+        Ok("ok".to_string())
+    } else {
+        Err(format!("wrong, {}", wrong.join(", ")))
+    }
+}
+
+/// Reads lines like `06 5101 ?`, giving the day and both answers with `?` for unknown ones.
+/// Blank lines are skipped, and each day may only be listed once.
+fn parse_answers(input: &str) -> Result<Answers, ParseError> {
+    let mut answers = Answers::new();
+    for (idx, line) in input.lines().enumerate() {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        if tokens.is_empty() {
+            continue;
+        }
+        let [day, part_1, part_2] = tokens[..] else {
+            let column = tokens
+                .get(3)
+                .map_or(line.len(), |t| parse::offset_of(line, t));
+            return Err(ParseError::new(
+                idx,
+                line,
+                column,
+                format!("line has {} fields", tokens.len()),
+                "a day followed by two answers",
+            ));
+        };
+
+        let answer = |token: &str| match token {
+            "?" => Ok(None),
+            _ => parse::number::<i64>(idx, line, token).map(Some),
+        };
+        let parts = [answer(part_1)?, answer(part_2)?];
+        let number = parse::number::<usize>(idx, line, day)?;
+        if answers.insert(number, parts).is_some() {
+            return Err(ParseError::at(
+                idx,
+                line,
+                day,
+                format!("day {} is listed twice", number),
+                "each day on one line only",
+            ));
+        }
+    }
+    Ok(answers)
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = parse_answers("01 11 31\n06 41 ?\n").unwrap();
+        assert_eq!(answers[&1], [Some(11), Some(31)]);
+        assert_eq!(answers[&6], [Some(41), None]);
+
+        let error = parse_answers("01 11 31\n02 2\n").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 5));
+
+        let answers = parse_answers("01 11 31\n\n  \n02 5 ?\n").unwrap();
+        assert_eq!(answers.len(), 2);
+        let error = parse_answers("01 11 31\n02 5 ?\n 01 12 31\n").unwrap_err();
+        assert_eq!((error.line(), error.column()), (3, 2));
+    }
+
+    #[test]
+    fn test_check() {
+        let solution = Solution {
+            part_1: 11,
+            part_2: 31,
+            parse_time: Default::default(),
+            part_1_time: Default::default(),
+            part_2_time: Default::default(),
+        };
+        assert_eq!(
+            check(&solution, Some(&[Some(11), None])),
+            Ok("ok".to_string())
+        );
+        assert_eq!(
+            check(&solution, Some(&[Some(11), Some(30)])),
+            Err("wrong, part 2 expected 30".to_string())
+        );
+        assert!(check(&solution, None).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::direction::{Dir4, Direction};
+use crate::point::Point;
+
+/// A fixed number of bits packed into words, all starting out unset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Bits {
+    words: Vec<u64>,
+}
+
+impl Bits {
+    fn new(len: usize) -> Bits {
+        Bits {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    fn get(&self, index: usize) -> bool {
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Sets the bit, returning whether it was unset before.
+    fn set(&mut self, index: usize) -> bool {
+        let word = &mut self.words[index / 64];
+        let mask = 1 << (index % 64);
+        let was_unset = *word & mask == 0;
+        *word |= mask;
+        was_unset
+    }
+
+    /// Unsets the bit, returning whether it was set before.
+    fn unset(&mut self, index: usize) -> bool {
+        let word = &mut self.words[index / 64];
+        let mask = 1 << (index % 64);
+        let was_set = *word & mask != 0;
+        *word &= !mask;
+        was_set
+    }
+
+    fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+/// One bit per cell of a `width` by `height` rectangle, for tracking visited cells apart from
+/// the map they belong to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitGrid {
+    bits: Bits,
+    width: usize,
+    height: usize,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> BitGrid {
+        BitGrid {
+            bits: Bits::new(width * height),
+            width,
+            height,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[allow(dead_code)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the cell at `point` is set, which is never the case outside of the grid.
+    pub fn contains(&self, point: Point) -> bool {
+        point
+            .to_index(self.width, self.height)
+            .is_some_and(|index| self.bits.get(index))
+    }
+
+    /// Sets the cell at `point`, returning whether it was unset before.
+    pub fn insert(&mut self, point: Point) -> bool {
+        self.bits.set(self.index(point))
+    }
+
+    /// Unsets the cell at `point`, returning whether it was set before.
+    #[allow(dead_code)]
+    pub fn remove(&mut self, point: Point) -> bool {
+        self.bits.unset(self.index(point))
+    }
+
+    /// Unsets every cell, keeping the allocation.
+    #[allow(dead_code)]
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    /// The number of set cells.
+    pub fn count(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    /// The set cells in reading order.
+    #[allow(dead_code)]
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.width * self.height)
+            .filter(|&index| self.bits.get(index))
+            .map(|index| Point::from_index(index, self.width))
+    }
+
+    fn index(&self, point: Point) -> usize {
+        point
+            .to_index(self.width, self.height)
+            .unwrap_or_else(|| panic!("{} is outside of the grid", point))
+    }
+}
+
+/// Four bits per cell of a `width` by `height` rectangle, one for each [`Dir4`], for tracking
+/// which ways a cell was passed through.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct DirectionGrid {
+    bits: Bits,
+    width: usize,
+    height: usize,
+}
+
+#[allow(dead_code)]
+impl DirectionGrid {
+    pub fn new(width: usize, height: usize) -> DirectionGrid {
+        DirectionGrid {
+            bits: Bits::new(width * height * 4),
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the cell at `point` is set for `dir`, which is never the case outside of the grid.
+    pub fn contains(&self, point: Point, dir: Dir4) -> bool {
+        point
+            .to_index(self.width, self.height)
+            .is_some_and(|index| self.bits.get(index * 4 + dir.index()))
+    }
+
+    /// Whether the cell at `point` is set for any direction.
+    pub fn contains_any(&self, point: Point) -> bool {
+        Dir4::all().any(|dir| self.contains(point, dir))
+    }
+
+    /// Sets the cell at `point` for `dir`, returning whether it was unset before.
+    pub fn insert(&mut self, point: Point, dir: Dir4) -> bool {
+        self.bits.set(self.index(point, dir))
+    }
+
+    /// Unsets the cell at `point` for `dir`, returning whether it was set before.
+    pub fn remove(&mut self, point: Point, dir: Dir4) -> bool {
+        self.bits.unset(self.index(point, dir))
+    }
+
+    /// Unsets every cell and direction, keeping the allocation.
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    /// The number of set `(cell, direction)` pairs.
+    pub fn count(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    /// The number of cells set for at least one direction.
+    pub fn count_cells(&self) -> usize {
+        // Fold each nibble onto its lowest bit, then count those
+        const LOWEST: u64 = 0x1111_1111_1111_1111;
+        self.bits
+            .words
+            .iter()
+            .map(|&word| {
+                let pairs = word | (word >> 1);
+                ((pairs | (pairs >> 2)) & LOWEST).count_ones() as usize
+            })
+            .sum()
+    }
+
+    fn index(&self, point: Point, dir: Dir4) -> usize {
+        let index = point
+            .to_index(self.width, self.height)
+            .unwrap_or_else(|| panic!("{} is outside of the grid", point));
+        index * 4 + dir.index()
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bit_grid() {
+        let mut grid = BitGrid::new(9, 9);
+        assert!(grid.insert(Point::new(8, 8)));
+        assert!(grid.insert(Point::new(3, 1)));
+        assert!(!grid.insert(Point::new(3, 1)));
+        assert!(grid.contains(Point::new(3, 1)));
+        assert!(!grid.contains(Point::new(9, 0)));
+        assert_eq!(grid.count(), 2);
+        assert_eq!(
+            grid.iter().collect::<Vec<_>>(),
+            [Point::new(3, 1), Point::new(8, 8)]
+        );
+
+        assert!(grid.remove(Point::new(8, 8)));
+        assert_eq!(grid.count(), 1);
+        grid.clear();
+        assert_eq!(grid.count(), 0);
+    }
+
+    #[test]
+    fn test_direction_grid() {
+        let mut grid = DirectionGrid::new(20, 1);
+        assert!(grid.insert(Point::new(17, 0), Dir4::Up));
+        assert!(grid.insert(Point::new(17, 0), Dir4::Left));
+        assert!(grid.insert(Point::new(2, 0), Dir4::Down));
+        assert!(!grid.insert(Point::new(17, 0), Dir4::Up));
+        assert!(grid.contains(Point::new(17, 0), Dir4::Left));
+        assert!(!grid.contains(Point::new(17, 0), Dir4::Right));
+        assert!(grid.contains_any(Point::new(2, 0)));
+        assert_eq!((grid.count(), grid.count_cells()), (3, 2));
+
+        grid.clear();
+        assert!(!grid.contains_any(Point::new(17, 0)));
+    }
+}
@@ -0,0 +1,219 @@
+//! Parsing straight from the raw bytes of an input, without UTF-8 validation or copies, for
+//! days whose inputs are plain ASCII numbers and separators.
+
+use std::str::Utf8Error;
+
+use crate::parse::ParseError;
+
+/// A cursor over one line of input that reads integers and separators off its front.
+#[derive(Clone, Debug)]
+pub struct Scanner<'a> {
+    line: usize,
+    source: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Scanner<'a> {
+    /// `line` is the zero-based index of `source`, used in errors.
+    pub fn new(line: usize, source: &'a [u8]) -> Scanner<'a> {
+        Scanner {
+            line,
+            source,
+            position: 0,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The part of the line not read yet.
+    pub fn rest(&self) -> &'a [u8] {
+        &self.source[self.position..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position == self.source.len()
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.source.get(self.position).copied()
+    }
+
+    /// Skips any number of `byte`, returning how many there were.
+    #[allow(dead_code)]
+    pub fn skip(&mut self, byte: u8) -> usize {
+        let count = self.rest().iter().take_while(|&&b| b == byte).count();
+        self.position += count;
+        count
+    }
+
+    /// Reads exactly `literal`, such as a separator.
+    pub fn expect(&mut self, literal: &[u8]) -> Result<(), ParseError> {
+        if !self.rest().starts_with(literal) {
+            let literal = String::from_utf8_lossy(literal);
+            return Err(self.error("missing separator", format!("{:?}", literal)));
+        }
+        self.position += literal.len();
+        Ok(())
+    }
+
+    /// Reads one or more digits.
+    pub fn unsigned(&mut self) -> Result<u64, ParseError> {
+        let start = self.position;
+        let mut value = 0u64;
+        while let Some(digit @ b'0'..=b'9') = self.peek() {
+            value = value
+                .checked_mul(10)
+                .and_then(|value| value.checked_add((digit - b'0') as u64))
+                .ok_or_else(|| self.error_at(start, "number out of range", "a u64"))?;
+            self.position += 1;
+        }
+        if self.position == start {
+            return Err(self.error("missing number", "a digit"));
+        }
+        Ok(value)
+    }
+
+    /// Reads digits with an optional leading sign.
+    pub fn signed(&mut self) -> Result<i64, ParseError> {
+        let start = self.position;
+        let negative = match self.peek() {
+            Some(b'-') => true,
+            Some(b'+') => false,
+            _ => {
+                return self
+                    .unsigned()
+                    .and_then(|value| self.to_signed(start, value))
+            }
+        };
+        self.position += 1;
+        let magnitude = self.unsigned()?;
+        if negative {
+            // The magnitude of i64::MIN does not fit into an i64 itself
+            0i64.checked_sub_unsigned(magnitude)
+                .ok_or_else(|| self.error_at(start, "number out of range", "an i64"))
+        } else {
+            self.to_signed(start, magnitude)
+        }
+    }
+
+    /// Fails unless the whole line has been read.
+    pub fn end(&self) -> Result<(), ParseError> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(self.error("unexpected trailing characters", "the end of the line")),
+        }
+    }
+
+    /// An error pointing at the current position.
+    pub fn error(&self, message: impl Into<String>, expected: impl Into<String>) -> ParseError {
+        self.error_at(self.position, message, expected)
+    }
+
+    fn error_at(
+        &self,
+        position: usize,
+        message: impl Into<String>,
+        expected: impl Into<String>,
+    ) -> ParseError {
+        error(self.line, self.source, position, message, expected)
+    }
+
+    fn to_signed(&self, start: usize, value: u64) -> Result<i64, ParseError> {
+        i64::try_from(value).map_err(|_| self.error_at(start, "number out of range", "an i64"))
+    }
+}
+
+/// A [`Scanner`] for every line of `input`, split like [`str::lines`].
+pub fn lines(input: &[u8]) -> impl Iterator<Item = Scanner<'_>> {
+    let input = input.strip_suffix(b"\n").unwrap_or(input);
+    input
+        .split(|&b| b == b'\n')
+        .filter(move |_| !input.is_empty())
+        .enumerate()
+        .map(|(idx, line)| Scanner::new(idx, line.strip_suffix(b"\r").unwrap_or(line)))
+}
+
+/// Builds a [`ParseError`] at byte `column` of `source`, replacing any invalid UTF-8 for display.
+pub fn error(
+    line: usize,
+    source: &[u8],
+    column: usize,
+    message: impl Into<String>,
+    expected: impl Into<String>,
+) -> ParseError {
+    let column = column.min(source.len());
+    let before = String::from_utf8_lossy(&source[..column]).len();
+    let source = String::from_utf8_lossy(source);
+    ParseError::new(line, &source, before, message, expected)
+}
+
+/// The error for an input that is not UTF-8, pointing at the first invalid byte.
+pub fn invalid_utf8(input: &[u8], utf8_error: Utf8Error) -> ParseError {
+    let offset = utf8_error.valid_up_to();
+    let line_start = input[..offset]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |newline| newline + 1);
+    let line_end = input[offset..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(input.len(), |newline| offset + newline);
+    let line = input[..line_start].iter().filter(|&&b| b == b'\n').count();
+    error(
+        line,
+        &input[line_start..line_end],
+        offset - line_start,
+        "invalid UTF-8",
+        "text",
+    )
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scanner() {
+        let mut line = Scanner::new(0, b"-12 +7   18446744073709551615,x");
+        assert_eq!(line.signed(), Ok(-12));
+        assert_eq!(line.skip(b' '), 1);
+        assert_eq!(line.signed(), Ok(7));
+        line.expect(b"   ").unwrap();
+        assert_eq!(line.peek(), Some(b'1'));
+        assert_eq!(line.unsigned(), Ok(u64::MAX));
+        assert_eq!(line.rest(), b",x");
+        assert_eq!(line.end().unwrap_err().column(), 30);
+        line.expect(b",").unwrap();
+        assert_eq!(line.unsigned().unwrap_err().column(), 31);
+
+        let min = Scanner::new(0, b"-9223372036854775808").signed();
+        assert_eq!(min, Ok(i64::MIN));
+        let error = Scanner::new(3, b"9223372036854775808")
+            .signed()
+            .unwrap_err();
+        assert_eq!((error.line(), error.column()), (4, 1));
+    }
+
+    #[test]
+    fn test_lines() {
+        fn rests(input: &[u8]) -> Vec<&[u8]> {
+            lines(input).map(|line| line.rest()).collect()
+        }
+        assert_eq!(rests(b"1 2\r\n\n3\n"), [&b"1 2"[..], b"", b"3"]);
+        assert_eq!(rests(b"1\n2"), [b"1", b"2"]);
+        assert!(rests(b"").is_empty());
+        assert_eq!(lines(b"a\nb").last().unwrap().line(), 1);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let input = b"12\n3\xff4\n".to_vec();
+        let error = invalid_utf8(&input, std::str::from_utf8(&input).unwrap_err());
+        assert_eq!((error.line(), error.column()), (2, 2));
+        assert!(error.to_string().contains("3\u{fffd}4"));
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A multiset counting how often each key occurs. Keys that do not occur are never stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Counter<K: Eq + Hash> {
+    counts: HashMap<K, usize>,
+}
+
+impl<K: Eq + Hash> Counter<K> {
+    pub fn new() -> Counter<K> {
+        Counter {
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, key: K) {
+        self.add_n(key, 1);
+    }
+
+    pub fn add_n(&mut self, key: K, n: usize) {
+        if n > 0 {
+            *self.counts.entry(key).or_insert(0) += n;
+        }
+    }
+
+    /// Takes away up to `n` occurrences of `key`, returning how many there were.
+    #[allow(dead_code)]
+    pub fn remove_n(&mut self, key: &K, n: usize) -> usize {
+        let Some(count) = self.counts.get_mut(key) else {
+            return 0;
+        };
+        let before = *count;
+        *count = before.saturating_sub(n);
+        if *count == 0 {
+            self.counts.remove(key);
+        }
+        before
+    }
+
+    /// How often `key` occurs, which is zero for keys never added.
+    pub fn get(&self, key: &K) -> usize {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    /// The number of distinct keys.
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The number of occurrences of all keys together.
+    #[allow(dead_code)]
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Every key with its count, in no particular order.
+    #[allow(dead_code)]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, usize)> {
+        self.counts.iter().map(|(key, &count)| (key, count))
+    }
+
+    /// Every key with its count, the most common first and ties in key order.
+    #[allow(dead_code)]
+    pub fn by_count(&self) -> Vec<(&K, usize)>
+    where
+        K: Ord,
+    {
+        let mut counts = self.iter().collect::<Vec<_>>();
+        counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        counts
+    }
+
+    /// The `n` most common keys with their counts, ordered like [`Counter::by_count`].
+    #[allow(dead_code)]
+    pub fn most_common(&self, n: usize) -> Vec<(&K, usize)>
+    where
+        K: Ord,
+    {
+        let mut counts = self.by_count();
+        counts.truncate(n);
+        counts
+    }
+}
+
+#[allow(dead_code)]
+impl<K: Clone + Eq + Hash> Counter<K> {
+    /// Each key as often as in whichever counter has more of it.
+    pub fn union(&self, other: &Counter<K>) -> Counter<K> {
+        let mut union = self.clone();
+        for (key, count) in other.iter() {
+            let entry = union.counts.entry(key.clone()).or_insert(0);
+            *entry = (*entry).max(count);
+        }
+        union
+    }
+
+    /// Each key as often as in whichever counter has less of it.
+    pub fn intersection(&self, other: &Counter<K>) -> Counter<K> {
+        self.iter()
+            .filter_map(|(key, count)| {
+                let count = count.min(other.get(key));
+                (count > 0).then(|| (key.clone(), count))
+            })
+            .collect()
+    }
+
+    /// Each key as often as it occurs here beyond its occurrences in `other`.
+    pub fn difference(&self, other: &Counter<K>) -> Counter<K> {
+        self.iter()
+            .filter_map(|(key, count)| {
+                let count = count.saturating_sub(other.get(key));
+                (count > 0).then(|| (key.clone(), count))
+            })
+            .collect()
+    }
+}
+
+impl<K: Eq + Hash> Default for Counter<K> {
+    fn default() -> Self {
+        Counter::new()
+    }
+}
+
+impl<K: Eq + Hash> FromIterator<K> for Counter<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(keys: I) -> Self {
+        let mut counter = Counter::new();
+        counter.extend(keys);
+        counter
+    }
+}
+
+impl<K: Eq + Hash> FromIterator<(K, usize)> for Counter<K> {
+    fn from_iter<I: IntoIterator<Item = (K, usize)>>(counts: I) -> Self {
+        let mut counter = Counter::new();
+        for (key, n) in counts {
+            counter.add_n(key, n);
+        }
+        counter
+    }
+}
+
+impl<K: Eq + Hash> Extend<K> for Counter<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, keys: I) {
+        for key in keys {
+            self.add(key);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let mut counter = "abracadabra".chars().collect::<Counter<_>>();
+        assert_eq!((counter.get(&'a'), counter.get(&'z')), (5, 0));
+        assert_eq!((counter.len(), counter.total()), (5, 11));
+        assert_eq!(counter.most_common(2), [(&'a', 5), (&'b', 2)]);
+        assert_eq!(
+            counter.by_count(),
+            [(&'a', 5), (&'b', 2), (&'r', 2), (&'c', 1), (&'d', 1)]
+        );
+
+        assert_eq!(counter.remove_n(&'c', 3), 1);
+        assert_eq!(counter.len(), 4);
+    }
+
+    #[test]
+    fn test_multiset_operations() {
+        let a = "aaabbc".chars().collect::<Counter<_>>();
+        let b = "abbbd".chars().collect::<Counter<_>>();
+        assert_eq!(a.union(&b), "aaabbbcd".chars().collect());
+        assert_eq!(a.intersection(&b), "abb".chars().collect());
+        assert_eq!(a.difference(&b), "aac".chars().collect());
+        assert!(a.difference(&a).is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// How a deterministic state machine ends up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The machine stopped after taking `steps` steps.
+    Exits { steps: usize },
+    /// After `prefix` steps the machine revisits the same `length` states forever.
+    Cycles { prefix: usize, length: usize },
+}
+
+impl Outcome {
+    pub fn is_cycle(&self) -> bool {
+        matches!(self, Outcome::Cycles { .. })
+    }
+}
+
+/// Runs `step` from `start` until it returns `None` or a state repeats, remembering every
+/// state seen. Uses memory linear in the number of steps but calls `step` once per state.
+#[allow(dead_code)]
+pub fn detect_with_set<S: Clone + Eq + Hash>(
+    start: S,
+    mut step: impl FnMut(&S) -> Option<S>,
+) -> Outcome {
+    let mut seen = HashMap::new();
+    let mut state = start;
+    let mut steps = 0;
+    loop {
+        if let Some(&first) = seen.get(&state) {
+            return Outcome::Cycles {
+                prefix: first,
+                length: steps - first,
+            };
+        }
+        let Some(next) = step(&state) else {
+            return Outcome::Exits { steps };
+        };
+        seen.insert(state, steps);
+        state = next;
+        steps += 1;
+    }
+}
+
+/// Like [`detect_with_set`], but uses Brent's algorithm to run in constant memory at the
+/// price of calling `step` up to three times as often.
+pub fn detect_brent<S: Clone + Eq>(start: S, mut step: impl FnMut(&S) -> Option<S>) -> Outcome {
+    // Find the cycle length by teleporting the tortoise to the hare at every power of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = start.clone();
+    let Some(mut hare) = step(&start) else {
+        return Outcome::Exits { steps: 0 };
+    };
+    let mut steps = 1;
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        let Some(next) = step(&hare) else {
+            return Outcome::Exits { steps };
+        };
+        hare = next;
+        steps += 1;
+        length += 1;
+    }
+
+    // Walk two states `length` apart from the start until they meet at the cycle entry.
+    // The machine is known to cycle now, so `step` never returns `None` again.
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..length {
+        hare = step(&hare).unwrap();
+    }
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise).unwrap();
+        hare = step(&hare).unwrap();
+        prefix += 1;
+    }
+
+    Outcome::Cycles { prefix, length }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cycles() {
+        // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 2
+        let step = |&n: &u32| Some(if n == 5 { 2 } else { n + 1 });
+        let expected = Outcome::Cycles {
+            prefix: 2,
+            length: 4,
+        };
+        assert_eq!(detect_with_set(0, step), expected);
+        assert_eq!(detect_brent(0, step), expected);
+
+        let loop_to_self = |&n: &u32| Some(n);
+        let expected = Outcome::Cycles {
+            prefix: 0,
+            length: 1,
+        };
+        assert_eq!(detect_with_set(7, loop_to_self), expected);
+        assert_eq!(detect_brent(7, loop_to_self), expected);
+    }
+
+    #[test]
+    fn test_exits() {
+        let step = |&n: &u32| (n < 5).then_some(n + 1);
+        assert_eq!(detect_with_set(0, step), Outcome::Exits { steps: 5 });
+        assert_eq!(detect_brent(0, step), Outcome::Exits { steps: 5 });
+        assert_eq!(detect_brent(9, step), Outcome::Exits { steps: 0 });
+    }
+}
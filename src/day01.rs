@@ -1,5 +1,8 @@
+use crate::bytes;
+use crate::counter::Counter;
+use crate::parse::ParseError;
+use crate::rng::Rng;
 use crate::AdventOfCodeDay;
-use std::collections::HashMap;
 
 pub struct Day;
 
@@ -7,20 +10,23 @@ impl AdventOfCodeDay for Day {
     const DAY: usize = 1;
     type Parsed = (Vec<i64>, Vec<i64>);
 
-    fn parse(input: String) -> Self::Parsed {
-        let (mut left, mut right): (Vec<i64>, Vec<i64>) = input
-            .lines()
-            .map(|line| {
-                line.split_once("   ")
-                    .map(|(a, b)| (a.parse::<i64>().unwrap(), b.parse::<i64>().unwrap()))
-                    .unwrap()
-            })
-            .unzip();
+    fn parse(input: String) -> Result<Self::Parsed, ParseError> {
+        Self::parse_bytes(input.as_bytes())
+    }
+
+    fn parse_bytes(input: &[u8]) -> Result<Self::Parsed, ParseError> {
+        let (mut left, mut right) = (vec![], vec![]);
+        for mut line in bytes::lines(input) {
+            left.push(line.signed()?);
+            line.expect(b"   ")?;
+            right.push(line.signed()?);
+            line.end()?;
+        }
 
         left.sort();
         right.sort();
 
-        (left, right)
+        Ok((left, right))
     }
 
     fn part_1((left, right): Self::Parsed) -> i64 {
@@ -31,16 +37,34 @@ impl AdventOfCodeDay for Day {
     }
 
     fn part_2((left, right): Self::Parsed) -> i64 {
-        let mut right_counts = HashMap::<i64, i64>::new();
-        right.iter().for_each(|&v| {
-            right_counts
-                .entry(v)
-                .and_modify(|counter| *counter += 1)
-                .or_insert(1);
-        });
+        let right_counts = right.into_iter().collect::<Counter<_>>();
 
-        left.iter()
-            .map(|l| l * right_counts.get(l).unwrap_or(&0))
-            .sum()
+        left.iter().map(|l| l * right_counts.get(l) as i64).sum()
+    }
+
+    /// One line per `size` with two five digit location ids.
+    fn generate(size: usize, rng: &mut Rng) -> Option<String> {
+        let lines = (0..size)
+            .map(|_| {
+                format!(
+                    "{}   {}",
+                    rng.range(10000..100000),
+                    rng.range(10000..100000)
+                )
+            })
+            .collect::<Vec<_>>();
+        Some(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tab_separator() {
+        let error = Day::parse("3   4\n12\t7\n".to_string()).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 3));
     }
 }
@@ -1,5 +1,9 @@
 use std::cmp::Ordering;
 
+use itertools::Itertools;
+
+use crate::parse::{self, ParseError};
+use crate::rng::Rng;
 use crate::AdventOfCodeDay;
 
 pub struct Day;
@@ -8,17 +12,8 @@ impl AdventOfCodeDay for Day {
     const DAY: usize = 2;
     type Parsed = Vec<Report>;
 
-    fn parse(input: String) -> Self::Parsed {
-        input
-            .lines()
-            .map(|line| {
-                let levels = line
-                    .split(" ")
-                    .map(|level| level.parse::<i64>().unwrap())
-                    .collect();
-                Report(levels)
-            })
-            .collect()
+    fn parse(input: String) -> Result<Self::Parsed, ParseError> {
+        parse::lines(&input, |idx, line| Ok(Report(parse::list(idx, line, " ")?)))
     }
 
     fn part_1(reports: Self::Parsed) -> i64 {
@@ -31,9 +26,26 @@ impl AdventOfCodeDay for Day {
             .filter(|report| report.is_safe_with_problem_dampener())
             .count() as i64
     }
+
+    /// `size` mostly monotone reports with five to eight levels each.
+    fn generate(size: usize, rng: &mut Rng) -> Option<String> {
+        let reports = (0..size)
+            .map(|_| {
+                let sign = if rng.one_in(2) { 1 } else { -1 };
+                let mut level = rng.range(10..90) as i64;
+                (0..rng.range(5..9))
+                    .map(|_| {
+                        level += sign * rng.range(0..5) as i64;
+                        level
+                    })
+                    .join(" ")
+            })
+            .collect_vec();
+        Some(reports.join("\n"))
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Report(Vec<i64>);
 
 impl Report {
@@ -67,3 +79,24 @@ impl Report {
         res.is_ok()
     }
 }
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.iter().join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_roundtrip(levels in prop::collection::vec(prop::collection::vec(-1000i64..1000, 1..10), 1..20)) {
+            let reports = levels.into_iter().map(Report).collect_vec();
+            let rendered = reports.iter().join("\n");
+            prop_assert_eq!(Day::parse(rendered).unwrap(), reports);
+        }
+    }
+}
@@ -1,5 +1,15 @@
+use crate::parse::ParseError;
+use crate::rng::Rng;
 use crate::AdventOfCodeDay;
 use regex::Regex;
+use std::sync::LazyLock;
+
+// Compiled once on first use, so timing the parts measures the scan alone
+static MUL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"mul\(([0-9]{1,3}),([0-9]{1,3})\)").unwrap());
+static INSTRUCTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(m)ul\(([0-9]{1,3}),([0-9]{1,3})\)|(d)o\(\)|do(n)'t\(\)").unwrap()
+});
 
 pub struct Day;
 
@@ -7,14 +17,12 @@ impl AdventOfCodeDay for Day {
     const DAY: usize = 3;
     type Parsed = String;
 
-    fn parse(input: String) -> Self::Parsed {
-        input
+    fn parse(input: String) -> Result<Self::Parsed, ParseError> {
+        Ok(input)
     }
 
     fn part_1(input: Self::Parsed) -> i64 {
-        let re = Regex::new(r"mul\(([0-9]{1,3}),([0-9]{1,3})\)").unwrap();
-
-        let input: Vec<(i64, i64)> = re
+        let input: Vec<(i64, i64)> = MUL
             .captures_iter(&input)
             .map(|cap| {
                 (
@@ -28,12 +36,10 @@ impl AdventOfCodeDay for Day {
     }
 
     fn part_2(input: Self::Parsed) -> i64 {
-        let re = Regex::new(r"(m)ul\(([0-9]{1,3}),([0-9]{1,3})\)|(d)o\(\)|do(n)'t\(\)").unwrap();
-
         let mut enabled = true;
         let mut sum = 0;
 
-        for capture in re.captures_iter(&input) {
+        for capture in INSTRUCTION.captures_iter(&input) {
             let (is_mul, is_do, is_dont) = (
                 capture.get(1).is_some(),
                 capture.get(4).is_some(),
@@ -51,4 +57,29 @@ impl AdventOfCodeDay for Day {
         }
         sum
     }
+
+    /// `size` instructions, valid or corrupted, separated by junk characters.
+    fn generate(size: usize, rng: &mut Rng) -> Option<String> {
+        let mut input = String::new();
+        for _ in 0..size {
+            match rng.range(0..6) {
+                0 => input.push_str("do()"),
+                1 => input.push_str("don't()"),
+                2 => input.push_str(&format!(
+                    "mul({},{}]",
+                    rng.range(0..1000),
+                    rng.range(0..1000)
+                )),
+                _ => input.push_str(&format!(
+                    "mul({},{})",
+                    rng.range(0..1000),
+                    rng.range(0..1000)
+                )),
+            }
+            for _ in 0..rng.range(0..8) {
+                input.push(*rng.pick(&['x', '%', '&', '!', '@', ' ', '[', ']', '(', ')']));
+            }
+        }
+        Some(input)
+    }
 }
@@ -1,3 +1,8 @@
+use itertools::Itertools;
+
+use crate::digraph::Digraph;
+use crate::parse::{self, ParseError};
+use crate::rng::Rng;
 use crate::AdventOfCodeDay;
 
 pub struct Day;
@@ -6,32 +11,52 @@ impl AdventOfCodeDay for Day {
     const DAY: usize = 5;
     type Parsed = Input;
 
-    fn parse(input: String) -> Self::Parsed {
-        let (orderings, updates) = input.split_once("\n\n").unwrap();
-
-        let rules = Rules::from(
-            orderings
-                .lines()
-                .map(|line| {
-                    line.split_once("|")
-                        .map(|(a, b)| (a.parse::<usize>().unwrap(), b.parse::<usize>().unwrap()))
-                        .unwrap()
-                })
-                .collect::<Vec<_>>(),
-        );
-
-        let updates = updates
-            .lines()
-            .map(|line| {
-                Update(
-                    line.split(",")
-                        .map(|split| split.parse::<usize>().unwrap())
-                        .collect(),
-                )
-            })
-            .collect();
+    fn parse(input: String) -> Result<Self::Parsed, ParseError> {
+        let sections = parse::sections(&input);
+        let (orderings, (offset, updates)) = match sections[..] {
+            [(_, orderings), updates] => (orderings, updates),
+            [_, _, (extra, section), ..] => {
+                return Err(ParseError::new(
+                    extra,
+                    section.lines().next().unwrap(),
+                    0,
+                    "unexpected extra blank line before this line",
+                    "a single blank line between rules and updates",
+                ))
+            }
+            _ => {
+                let last = input.lines().count().saturating_sub(1);
+                let line = input.lines().nth(last).unwrap_or("");
+                return Err(ParseError::new(
+                    last,
+                    line,
+                    line.len(),
+                    "missing the blank line between rules and updates",
+                    "an empty line",
+                ));
+            }
+        };
+
+        let rules = Rules::from(parse::lines(orderings, |idx, line| {
+            parse::pair(idx, line, "|")
+        })?);
+        let updates = parse::lines(updates, |idx, line| {
+            let pages = parse::list::<usize>(idx, line, ",")?;
+            // The middle page is only well defined if every page appears once
+            match (1..pages.len()).find(|&i| pages[..i].contains(&pages[i])) {
+                Some(repeated) => Err(ParseError::at(
+                    idx,
+                    line,
+                    line.split(',').nth(repeated).unwrap(),
+                    format!("page {} appears twice", pages[repeated]),
+                    "each page at most once",
+                )),
+                None => Ok(Update(pages)),
+            }
+        })
+        .map_err(|error| error.offset_lines(offset))?;
 
-        Input { rules, updates }
+        Ok(Input { rules, updates })
     }
 
     fn part_1(Input { rules, updates }: Self::Parsed) -> i64 {
@@ -45,57 +70,80 @@ impl AdventOfCodeDay for Day {
     fn part_2(Input { rules, updates }: Self::Parsed) -> i64 {
         updates
             .iter()
-            .filter_map(|update| {
-                let rules = rules.get_applicable_for(update);
-                if !update.allowed_by(&rules) {
-                    Some(update.order_by(&rules))
-                } else {
-                    None
-                }
-            })
+            .filter(|&update| !update.allowed_by(&rules))
+            .map(|update| update.order_by(&rules))
             .map(|update| update.middle())
             .sum::<usize>() as i64
     }
+
+    /// `size` updates over `5 + size / 4` pages, with a rule for every pair of pages
+    /// like the real input has. The page numbers are drawn from a range twice that long, so
+    /// they are not limited to the two digits the real input uses and keep growing with `size`.
+    fn generate(size: usize, rng: &mut Rng) -> Option<String> {
+        let count = 5 + size / 4;
+        let mut pages = (10..10 + 2 * count).collect_vec();
+        rng.shuffle(&mut pages);
+        pages.truncate(count);
+
+        let mut rules = pages.iter().tuple_combinations::<(_, _)>().collect_vec();
+        rng.shuffle(&mut rules);
+        let rules = rules.iter().map(|(a, b)| format!("{}|{}", a, b)).join("\n");
+
+        let updates = (0..size)
+            .map(|_| {
+                let mut update = pages.clone();
+                rng.shuffle(&mut update);
+                update.truncate(rng.range(2..pages.len() / 2 + 1) * 2 - 1);
+                update.iter().join(",")
+            })
+            .join("\n");
+        Some(format!("{}\n\n{}", rules, updates))
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Input {
     rules: Rules,
     updates: Vec<Update>,
 }
 
-#[derive(Clone)]
-struct Rules(Vec<(usize, usize)>);
+/// The ordering rules as a graph with an edge from each page to the pages that must follow it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Rules(Digraph<usize>);
 
 impl Rules {
+    /// Whether `pair` may appear in this order, which it may unless a rule asks for the inverse.
     fn allow(&self, pair: (usize, usize)) -> bool {
-        // if any rule disallows an inverted pair we return false
-        !self
-            .0
-            .iter()
-            .any(|rule| (rule.0, rule.1) == (pair.1, pair.0))
+        !self.0.has_edge(&pair.1, &pair.0)
     }
+}
 
-    fn get_applicable_for(&self, update: &Update) -> Rules {
-        let applicable = self
-            .0
-            .iter()
-            .filter(|(a, b)| update.contains(a) && update.contains(b))
-            .cloned()
-            .collect::<Vec<_>>();
-        Rules::from(applicable)
+impl std::fmt::Display for Input {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\n\n{}", self.rules, self.updates.iter().join("\n"))
+    }
+}
+
+impl std::fmt::Display for Rules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.0
+                .edges()
+                .map(|(a, b)| format!("{}|{}", a, b))
+                .join("\n")
+        )
     }
 }
 
 impl From<Vec<(usize, usize)>> for Rules {
     fn from(value: Vec<(usize, usize)>) -> Self {
-        let mut value = value;
-        value.sort();
-        Rules(value)
+        Rules(value.into_iter().collect())
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 struct Update(Vec<usize>);
 
 impl Update {
@@ -108,7 +156,6 @@ impl Update {
     }
 
     fn allowed_by(&self, rules: &Rules) -> bool {
-        let rules = rules.get_applicable_for(self);
         for left in 0..(self.0.len() - 1) {
             for right in (left + 1)..(self.0.len()) {
                 if !rules.allow((self.0[left], self.0[right])) {
@@ -120,28 +167,26 @@ impl Update {
     }
 
     fn order_by(&self, rules: &Rules) -> Update {
-        let mut entries = self.0.clone();
-        let len = entries.len();
-
-        for left in 0..(len - 1) {
-            'foo: loop {
-                for right in left..len {
-                    if !rules.allow((entries[left], entries[right])) {
-                        entries.swap(left, right);
-                        continue 'foo;
-                    }
-                }
-                break 'foo;
-            }
-        }
+        let mut graph = rules.0.induced(|page| self.contains(page));
+        self.0.iter().for_each(|&page| graph.add_node(page));
+        let ordered = graph
+            .topological_sort()
+            .unwrap_or_else(|cycle| panic!("rules for {} form a cycle {:?}", self, cycle));
+        Update(ordered)
+    }
+}
 
-        Update(entries)
+impl std::fmt::Display for Update {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.iter().join(","))
     }
 }
 
+#[cfg(test)]
 #[allow(unused_imports)]
 mod test {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_rules() {
@@ -160,5 +205,42 @@ mod test {
         let updates = [Update(vec![1, 2, 3]), Update(vec![2, 1, 3])];
         assert!(updates[0].allowed_by(&rules));
         assert!(!updates[1].allowed_by(&rules));
+        assert_eq!(updates[1].order_by(&rules), updates[0]);
+    }
+
+    #[test]
+    fn test_sections() {
+        let error = Day::parse("1|2\n2|3\n".to_string()).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 4));
+        let error = Day::parse("1|2\n\n1,2,3\n\n2,3,4\n".to_string()).unwrap_err();
+        assert_eq!((error.line(), error.column()), (5, 1));
+        assert!(error.to_string().contains("extra blank line"));
+    }
+
+    #[test]
+    fn test_repeated_page() {
+        let error = Day::parse("1|2\n\n1,2,3\n4,2,5,2\n".to_string()).unwrap_err();
+        assert_eq!((error.line(), error.column()), (4, 7));
+    }
+
+    fn input() -> impl Strategy<Value = Input> {
+        (
+            prop::collection::vec((10..100usize, 10..100usize), 1..30),
+            prop::collection::vec(prop::collection::hash_set(10..100usize, 1..10), 1..10),
+        )
+            .prop_map(|(rules, updates)| Input {
+                rules: Rules::from(rules),
+                updates: updates
+                    .into_iter()
+                    .map(|pages| Update(pages.into_iter().collect()))
+                    .collect(),
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_roundtrip(input in input()) {
+            prop_assert_eq!(Day::parse(input.to_string()).unwrap(), input);
+        }
     }
 }
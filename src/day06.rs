@@ -1,3 +1,11 @@
+use crate::bit_grid::BitGrid;
+use crate::cycle;
+use crate::direction::{Dir4, Direction};
+use crate::geometry::Segment;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::point::Point;
+use crate::rng::Rng;
 use crate::AdventOfCodeDay;
 
 pub struct Day;
@@ -6,223 +14,174 @@ impl AdventOfCodeDay for Day {
     const DAY: usize = 6;
     type Parsed = Map;
 
-    fn parse(input: String) -> Self::Parsed {
-        let width = input.lines().peekable().next().unwrap().len();
-        let height = input.lines().count();
-        let grid = input
-            .lines()
-            .flat_map(|line| {
-                line.chars().map(|c| match c {
-                    '.' => Tile::Empty,
-                    '#' => Tile::Obstructed,
-                    '^' => Tile::Guard(Dir::Up),
-                    'v' => Tile::Guard(Dir::Down),
-                    '<' => Tile::Guard(Dir::Left),
-                    '>' => Tile::Guard(Dir::Right),
-                    _ => unimplemented!(),
-                })
+    fn parse(input: String) -> Result<Self::Parsed, ParseError> {
+        let grid = Grid::parse(&input, |c| match c {
+            '.' => Ok(Tile::Empty),
+            '#' => Ok(Tile::Obstructed),
+            '^' => Ok(Tile::Guard(Dir4::Up)),
+            'v' => Ok(Tile::Guard(Dir4::Down)),
+            '<' => Ok(Tile::Guard(Dir4::Left)),
+            '>' => Ok(Tile::Guard(Dir4::Right)),
+            _ => Err("one of '.', '#', '^', 'v', '<' or '>'"),
+        })?;
+
+        let mut guards = grid
+            .enumerate()
+            .filter_map(|(point, tile)| match tile {
+                Tile::Guard(dir) => Some((point, *dir)),
+                _ => None,
             })
-            .collect::<Vec<Tile>>();
+            .take(2)
+            .collect::<Vec<_>>()
+            .into_iter();
+        let (guard, second) = (guards.next(), guards.next());
+        let guard = guard.ok_or_else(|| {
+            let first = input.lines().next().unwrap_or("");
+            ParseError::new(
+                0,
+                first,
+                0,
+                "map has no guard",
+                "one of '^', 'v', '<' or '>'",
+            )
+        })?;
+        if let Some((point, _)) = second {
+            let line = input.lines().nth(point.y as usize).unwrap();
+            let column = line.char_indices().nth(point.x as usize).unwrap().0;
+            return Err(ParseError::new(
+                point.y as usize,
+                line,
+                column,
+                "map has a second guard",
+                "a single guard",
+            ));
+        }
 
-        Map::new(grid, width, height)
+        Ok(Map { grid, guard })
     }
 
-    fn part_1(mut map: Self::Parsed) -> i64 {
-        while map.guard_idx.is_some() {
-            map.step();
+    fn part_1(map: Self::Parsed) -> i64 {
+        let mut visited = BitGrid::new(map.grid.width(), map.grid.height());
+        for (point, _) in map.path() {
+            visited.insert(point);
         }
-
-        map.grid
-            .iter()
-            .filter(|t| matches!(t, Tile::Marked))
-            .count() as i64
+        visited.count() as i64
     }
 
     fn part_2(map: Self::Parsed) -> i64 {
-        let lines = map.clone().get_all_lines();
-
-        // TODO: Find and pre-insert Patterns like: #----->#
-        //                                                #
-        let prepared_loops = vec![];
-
-        let mut obstacles = lines
-            .iter()
-            .fold(
-                (prepared_loops, vec![]),
-                |(mut looping_lines, mut obstacles), next| {
-                    for looping in &looping_lines {
-                        if let Some(obstacle) = try_get_obstacle(next, &looping, &map) {
-                            obstacles.push(obstacle);
-                        }
-                    }
-
-                    looping_lines.push(extend_line(&next, &map));
-                    (looping_lines, obstacles)
-                },
-            )
-            .1;
-
-        println!("Obstacles: {:?}", obstacles);
-        obstacles.sort();
-        obstacles.dedup();
-        obstacles.len() as i64
-    }
-}
-
-fn extend_line(line: &Line, map: &Map) -> Line {
-    let mut from = line.from;
-    let rev_dir = line.dir.rotate().rotate();
-    loop {
-        let new_from = rev_dir.step(from);
-        if map.out_of_bounds(new_from) || map.grid[map.index_from(new_from)] == Tile::Obstructed {
-            break;
+        let (start, _) = map.guard;
+        let lines = map.get_all_lines();
+
+        // Only cells on the original path can change where the guard goes. Each candidate is
+        // tried once, starting the guard just before it first walks onto that cell.
+        let mut tried = BitGrid::new(map.grid.width(), map.grid.height());
+        let mut loops = 0;
+        for line in &lines {
+            let dir = line.direction().unwrap();
+            for obstacle in line.points().skip(1) {
+                if obstacle == start || !tried.insert(obstacle) {
+                    continue;
+                }
+                let guard = (obstacle - dir.unit(), dir);
+                let patrol = |&state: &(Point, Dir4)| map.patrol(state, Some(obstacle));
+                if cycle::detect_brent(guard, patrol).is_cycle() {
+                    loops += 1;
+                }
+            }
         }
-        from = new_from;
+        loops
     }
-    Line::new(line.dir, from, line.to)
-}
 
-fn try_get_obstacle(next_line: &Line, prev_line: &Line, map: &Map) -> Option<(i64, i64)> {
-    if next_line.dir.rotate() != prev_line.dir {
-        return None;
-    }
+    /// A `size` by `size` map where the guard starts in the middle and walks an outward
+    /// spiral of obstructions until it leaves the map, so the path, and with it the number
+    /// of obstacles part 2 tries, grows with `size`. More obstructions are scattered off the
+    /// path, which leaves the path alone but lets some new obstacles cause loops.
+    fn generate(size: usize, rng: &mut Rng) -> Option<String> {
+        let mut grid = Grid::new(vec![Tile::Empty; size * size], size, size);
+        let mut path = BitGrid::new(size, size);
+        let start = Point::new(size as i64 / 2, size as i64 / 2);
+        let gap = (size / 16).max(2) as i64;
+
+        // Legs grow every second turn, so each ring runs `gap` outside the previous one
+        let (mut guard, mut dir) = (start, Dir4::Up);
+        path.insert(guard);
+        'spiral: for leg in 0.. {
+            for _ in 0..(leg / 2 + 1) * gap {
+                guard = dir.step(guard);
+                if !grid.in_bounds(guard) {
+                    break 'spiral;
+                }
+                path.insert(guard);
+            }
+            match grid.get_mut(dir.step(guard)) {
+                Some(tile) => *tile = Tile::Obstructed,
+                None => break,
+            }
+            dir = dir.turn_right();
+        }
 
-    if let Some(intersection) = next_line.intersects_with(&prev_line) {
-        //println!(
-        //    "Found intersection at {:?} for lines\n{:?}\n{:?}",
-        //    intersection, prev_line, next_line
-        //);
-        let new_obstacle_coord = next_line.dir.step(intersection);
-        if map.grid[map.index_from(new_obstacle_coord)] != Tile::Obstructed {
-            return Some(new_obstacle_coord);
+        for index in 0..size * size {
+            let point = grid.point_from(index);
+            if grid[point] == Tile::Empty && !path.contains(point) && rng.one_in(12) {
+                grid[point] = Tile::Obstructed;
+            }
         }
-    } else {
-        //println!(
-        //    "Found no intersection for lines\n{:?}\n{:?}",
-        //    prev_line, next_line
-        //);
+        grid[start] = Tile::Guard(Dir4::Up);
+        Some(grid.to_string())
     }
-
-    None
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Map {
-    grid: Vec<Tile>,
-    width: usize,
-    height: usize,
-    guard_idx: Option<usize>,
+    grid: Grid<Tile>,
+    guard: (Point, Dir4),
 }
 
 impl Map {
-    fn new(grid: Vec<Tile>, width: usize, height: usize) -> Map {
-        let guard_idx = grid
-            .iter()
-            .enumerate()
-            .find_map(|(index, t)| matches!(t, Tile::Guard(_)).then_some(index));
-
-        Map {
-            grid,
-            width,
-            height,
-            guard_idx,
-        }
-    }
-
-    fn step(&mut self) -> StepEvent {
-        if self.guard_idx.is_none() {
-            println!("simulated too much");
-            return StepEvent::Exit;
-        }
-        let guard_idx = self.guard_idx.unwrap();
-        let guard_coord = self.coordinate_from(guard_idx);
-        let guard_dir = self.unsafe_guard_dir();
-        let new_coord = guard_dir.step(guard_coord);
-
-        if self.out_of_bounds(new_coord) {
-            self.grid[guard_idx] = Tile::Marked;
-            self.guard_idx = None;
-            return StepEvent::Exit;
-        } else {
-            let new_idx = self.index_from(new_coord);
-
-            if self.is_obstructed(new_idx) {
-                let rotated = guard_dir.rotate();
-                self.grid[guard_idx] = Tile::Guard(rotated);
-                return StepEvent::Rotate(rotated);
-            } else {
-                self.grid[guard_idx] = Tile::Marked;
-                self.grid[new_idx] = Tile::Guard(guard_dir);
-                self.guard_idx = Some(new_idx);
-                return StepEvent::Forward;
-            }
-        }
+    /// Every state of the guard from the start until it leaves the map.
+    fn path(&self) -> impl Iterator<Item = (Point, Dir4)> + '_ {
+        std::iter::successors(Some(self.guard), |&state| self.patrol(state, None))
     }
 
-    fn get_all_lines(mut self) -> Vec<Line> {
-        let mut last_dir = self.unsafe_guard_dir();
-        let mut last_coord = self.coordinate_from(self.guard_idx.unwrap());
-
+    /// The guard's path split into the straight lines between turns.
+    fn get_all_lines(&self) -> Vec<Segment> {
         let mut lines = vec![];
-        let mut event = StepEvent::Forward;
-        loop {
-            if let StepEvent::Rotate(to) = event {
-                let coord = self.coordinate_from(self.guard_idx.unwrap());
-                if coord.ne(&last_coord) {
-                    lines.push(Line::new(last_dir, last_coord, coord));
-                }
-                last_dir = to;
-                last_coord = coord;
-            } else if let StepEvent::Exit = event {
-                let coord = match last_dir {
-                    Dir::Up => (last_coord.0, 0),
-                    Dir::Down => (last_coord.0, self.height as i64 - 1),
-                    Dir::Left => (0, last_coord.1),
-                    Dir::Right => (self.width as i64 - 1, last_coord.1),
-                };
-                if coord.ne(&last_coord) {
-                    lines.push(Line::new(last_dir, last_coord, coord));
+        let (mut from, mut dir) = self.guard;
+        let mut to = from;
+        for (point, next_dir) in self.path() {
+            if next_dir != dir {
+                if from != to {
+                    lines.push(Segment::new(from, to));
                 }
-                break;
+                (from, dir) = (point, next_dir);
             }
-            event = self.step();
+            to = point;
         }
-
-        lines
-    }
-
-    fn unsafe_guard_dir(&self) -> Dir {
-        match self.grid[self.guard_idx.unwrap()].clone() {
-            Tile::Guard(g) => g,
-            _ => unreachable!(),
+        if from != to {
+            lines.push(Segment::new(from, to));
         }
+        lines
     }
 
-    fn is_obstructed(&mut self, new_idx: usize) -> bool {
-        match self.grid[new_idx] {
-            Tile::Obstructed => true,
-            Tile::Empty => false,
-            Tile::Marked => false,
-            Tile::Guard(_) => true,
+    /// Moves a guard in state `(position, facing)` one step, treating `obstacle` as an extra
+    /// obstruction, or `None` once it leaves the map.
+    fn patrol(
+        &self,
+        (guard, dir): (Point, Dir4),
+        obstacle: Option<Point>,
+    ) -> Option<(Point, Dir4)> {
+        let next = dir.step(guard);
+        match self.grid.get(next)? {
+            Tile::Obstructed => Some((guard, dir.turn_right())),
+            _ if Some(next) == obstacle => Some((guard, dir.turn_right())),
+            _ => Some((next, dir)),
         }
     }
+}
 
-    fn out_of_bounds(&self, new_coord: (i64, i64)) -> bool {
-        new_coord.0 < 0
-            || new_coord.0 >= (self.width as i64)
-            || new_coord.1 < 0
-            || new_coord.1 >= (self.height as i64)
-    }
-
-    fn coordinate_from(&self, index: usize) -> (i64, i64) {
-        (
-            index.rem_euclid(self.width) as i64,
-            index.div_euclid(self.width) as i64,
-        )
-    }
-
-    fn index_from(&self, coord: (i64, i64)) -> usize {
-        coord.1 as usize * self.width + coord.0 as usize
+impl std::fmt::Display for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.grid)
     }
 }
 
@@ -230,113 +189,98 @@ impl Map {
 pub enum Tile {
     Empty,
     Obstructed,
-    Marked,
-    Guard(Dir),
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
+    Guard(Dir4),
 }
 
-impl Dir {
-    fn rotate(&self) -> Dir {
-        match self {
-            Dir::Up => Dir::Right,
-            Dir::Down => Dir::Left,
-            Dir::Left => Dir::Up,
-            Dir::Right => Dir::Down,
-        }
-    }
-
-    fn step(&self, coord: (i64, i64)) -> (i64, i64) {
-        match self {
-            Dir::Up => (coord.0, coord.1 - 1),
-            Dir::Down => (coord.0, coord.1 + 1),
-            Dir::Left => (coord.0 - 1, coord.1),
-            Dir::Right => (coord.0 + 1, coord.1),
-        }
-    }
-
-    fn is_horizontal(&self) -> bool {
-        match self {
-            Dir::Up => false,
-            Dir::Down => false,
-            Dir::Left => true,
-            Dir::Right => true,
-        }
+impl std::fmt::Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Tile::Empty => '.',
+            Tile::Obstructed => '#',
+            Tile::Guard(Dir4::Up) => '^',
+            Tile::Guard(Dir4::Down) => 'v',
+            Tile::Guard(Dir4::Left) => '<',
+            Tile::Guard(Dir4::Right) => '>',
+        };
+        write!(f, "{}", c)
     }
 }
 
-#[derive(Clone, Debug)]
-enum StepEvent {
-    Forward,
-    Rotate(Dir),
-    Exit,
-}
-
-#[derive(Clone, Debug)]
-struct Line {
-    dir: Dir,
-    from: (i64, i64),
-    to: (i64, i64),
-}
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use crate::day06::*;
+    use proptest::prelude::*;
 
-impl Line {
-    fn new(dir: Dir, from: (i64, i64), to: (i64, i64)) -> Line {
-        assert!(
-            (from.1 == to.1 && (matches!(dir, Dir::Left) || matches!(dir, Dir::Right)))
-                || (from.0 == to.0 && (matches!(dir, Dir::Up) || matches!(dir, Dir::Down)))
+    #[test]
+    fn test_lines() {
+        let input = ".#..\n....\n.^..\n";
+        let map = Day::parse(input.to_string()).unwrap();
+        assert_eq!(
+            map.get_all_lines(),
+            [
+                Segment::new(Point::new(1, 2), Point::new(1, 1)),
+                Segment::new(Point::new(1, 1), Point::new(3, 1)),
+            ]
         );
-        Line { dir, from, to }
     }
 
-    fn intersects_with(&self, other: &Line) -> Option<(i64, i64)> {
-        // Ensure that the lines do not start or end at the same point
-        // if self.from == other.from
-        //     || self.to == other.to
-        //     || self.from == other.to
-        //     || self.to == other.from
-        // {
-        //     return None;
-        // }
-
-        // parallel lines don't intersect in our terms
-        if self.dir.is_horizontal() == other.dir.is_horizontal() {
-            return None;
-        }
-
-        if self.dir.is_horizontal() && !other.dir.is_horizontal() {
-            let self_y = self.from.1;
-            let other_x = other.from.0;
+    #[test]
+    fn test_loops() {
+        let input = "....#.....\n.........#\n..........\n..#.......\n.......#..\n..........\n.#..^.....\n........#.\n#.........\n......#...";
+        let map = Day::parse(input.to_string()).unwrap();
+        assert_eq!(Day::part_1(map.clone()), 41);
+        assert_eq!(Day::part_2(map), 6);
+    }
 
-            // Check if the x of the vertical line is within the range of the horizontal line
-            if (self.from.0.min(self.to.0)..=self.from.0.max(self.to.0)).contains(&other_x)
-                && (other.from.1.min(other.to.1)..=other.from.1.max(other.to.1)).contains(&self_y)
-            {
-                return Some((other_x, self_y));
-            } else {
-                return None;
-            }
-        } else {
-            other.intersects_with(self)
-        }
+    #[test]
+    fn test_generated_spiral() {
+        let input = Day::generate(64, &mut crate::rng::Rng::new(6)).unwrap();
+        let map = Day::parse(input).unwrap();
+        let guard = map.guard;
+        assert!(!cycle::detect_brent(guard, |&state| map.patrol(state, None)).is_cycle());
+        // Several rings of the spiral, not a walk straight off the map
+        assert!(map.get_all_lines().len() >= 8);
     }
-}
 
-#[allow(unused_imports)]
-mod test {
-    use super::Line;
-    use crate::day06::*;
+    #[test]
+    fn test_guards() {
+        let error = Day::parse("..#\n...\n".to_string()).unwrap_err();
+        assert_eq!((error.line(), error.column()), (1, 1));
+        let error = Day::parse("..#\n^.<\n".to_string()).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 3));
+    }
 
     #[test]
-    fn test_lines() {
-        let line1 = Line::new(Dir::Right, (0, 1), (9, 1));
-        let line2 = Line::new(Dir::Down, (9, 0), (9, 9));
+    fn test_stray_character() {
+        let error = Day::parse("..#\n.^.\n#o.\n".to_string()).unwrap_err();
+        assert_eq!((error.line(), error.column()), (3, 2));
+    }
+
+    fn map() -> impl Strategy<Value = Map> {
+        (1..20usize, 1..20usize).prop_flat_map(|(width, height)| {
+            let tile = prop::sample::select(vec![Tile::Empty, Tile::Obstructed]);
+            let guard = prop::sample::select(Dir4::ALL);
+            (
+                prop::collection::vec(tile, width * height),
+                0..width * height,
+                guard,
+            )
+                .prop_map(move |(mut grid, guard, dir)| {
+                    grid[guard] = Tile::Guard(dir);
+                    let guard = (Point::from_index(guard, width), dir);
+                    Map {
+                        grid: Grid::new(grid, width, height),
+                        guard,
+                    }
+                })
+        })
+    }
 
-        assert!(line1.intersects_with(&line2).is_some());
+    proptest! {
+        #[test]
+        fn test_roundtrip(map in map()) {
+            prop_assert_eq!(Day::parse(map.to_string()).unwrap(), map);
+        }
     }
 }
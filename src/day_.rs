@@ -1,3 +1,4 @@
+use crate::parse::ParseError;
 use crate::AdventOfCodeDay;
 
 pub struct Day;
@@ -6,7 +7,7 @@ impl AdventOfCodeDay for Day {
     const DAY: usize = 0;
     type Parsed = ();
 
-    fn parse(_: String) -> Self::Parsed {
+    fn parse(_: String) -> Result<Self::Parsed, ParseError> {
         todo!()
     }
 
@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A directed graph over nodes of type `N`, stored as sorted adjacency sets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Digraph<N: Ord> {
+    edges: BTreeMap<N, BTreeSet<N>>,
+}
+
+impl<N: Ord + Clone> Digraph<N> {
+    pub fn new() -> Digraph<N> {
+        Digraph {
+            edges: BTreeMap::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.edges.entry(node).or_default();
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N) {
+        self.add_node(to.clone());
+        self.edges.entry(from).or_default().insert(to);
+    }
+
+    pub fn has_edge(&self, from: &N, to: &N) -> bool {
+        self.edges.get(from).is_some_and(|tos| tos.contains(to))
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.edges.keys()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (&N, &N)> {
+        self.edges
+            .iter()
+            .flat_map(|(from, tos)| tos.iter().map(move |to| (from, to)))
+    }
+
+    pub fn successors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.edges.get(node).into_iter().flatten()
+    }
+
+    #[allow(dead_code)]
+    pub fn edge_count(&self) -> usize {
+        self.edges.values().map(BTreeSet::len).sum()
+    }
+
+    /// The subgraph of the nodes accepted by `keep` and the edges between them.
+    pub fn induced(&self, mut keep: impl FnMut(&N) -> bool) -> Digraph<N> {
+        let edges = self
+            .edges
+            .iter()
+            .filter(|(node, _)| keep(node))
+            .map(|(node, tos)| (node.clone(), tos.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let kept = edges.keys().cloned().collect::<BTreeSet<_>>();
+        let edges = edges
+            .into_iter()
+            .map(|(node, tos)| (node, tos.intersection(&kept).cloned().collect()))
+            .collect();
+        Digraph { edges }
+    }
+
+    /// Orders the nodes so every edge points forward, using Kahn's algorithm. Ties are broken
+    /// by taking the smallest node that is ready. Returns a cycle if there is no such order.
+    pub fn topological_sort(&self) -> Result<Vec<N>, Vec<N>> {
+        let mut incoming = self
+            .nodes()
+            .map(|node| (node, 0))
+            .collect::<BTreeMap<_, usize>>();
+        for (_, to) in self.edges() {
+            *incoming.get_mut(to).unwrap() += 1;
+        }
+
+        let mut ready = incoming
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&node, _)| node)
+            .collect::<BTreeSet<_>>();
+        let mut order = Vec::with_capacity(self.edges.len());
+        while let Some(node) = ready.pop_first() {
+            order.push(node.clone());
+            for to in self.successors(node) {
+                let count = incoming.get_mut(to).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert(to);
+                }
+            }
+        }
+
+        if order.len() == self.edges.len() {
+            Ok(order)
+        } else {
+            Err(self.find_cycle().unwrap())
+        }
+    }
+
+    /// Finds some cycle, listing its nodes in order without repeating the first one.
+    pub fn find_cycle(&self) -> Option<Vec<N>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            Unvisited,
+            OnPath,
+            Done,
+        }
+
+        let mut marks = self
+            .nodes()
+            .map(|node| (node, Mark::Unvisited))
+            .collect::<BTreeMap<_, _>>();
+
+        for root in self.nodes() {
+            if marks[root] != Mark::Unvisited {
+                continue;
+            }
+            // Iterative DFS, keeping the current path and each node's remaining successors
+            let mut path = vec![root];
+            let mut pending = vec![self.successors(root)];
+            marks.insert(root, Mark::OnPath);
+
+            while let Some(successors) = pending.last_mut() {
+                match successors.next() {
+                    Some(next) => match marks[next] {
+                        Mark::Unvisited => {
+                            marks.insert(next, Mark::OnPath);
+                            path.push(next);
+                            pending.push(self.successors(next));
+                        }
+                        Mark::OnPath => {
+                            let start = path.iter().position(|&node| node == next).unwrap();
+                            return Some(path[start..].iter().map(|&node| node.clone()).collect());
+                        }
+                        Mark::Done => {}
+                    },
+                    None => {
+                        marks.insert(path.pop().unwrap(), Mark::Done);
+                        pending.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// The nodes reachable from `node` by following at least one edge.
+    #[allow(dead_code)]
+    pub fn reachable_from(&self, node: &N) -> BTreeSet<N> {
+        let mut reached = BTreeSet::new();
+        let mut stack = self.successors(node).collect::<Vec<_>>();
+        while let Some(next) = stack.pop() {
+            if reached.insert(next.clone()) {
+                stack.extend(self.successors(next));
+            }
+        }
+        reached
+    }
+
+    /// The graph with an edge from every node to each node reachable from it.
+    #[allow(dead_code)]
+    pub fn transitive_closure(&self) -> Digraph<N> {
+        let edges = self
+            .nodes()
+            .map(|node| (node.clone(), self.reachable_from(node)))
+            .collect();
+        Digraph { edges }
+    }
+
+    /// The smallest graph with the same reachability, which is only unique for acyclic
+    /// graphs. Returns a cycle if there is one.
+    #[allow(dead_code)]
+    pub fn transitive_reduction(&self) -> Result<Digraph<N>, Vec<N>> {
+        if let Some(cycle) = self.find_cycle() {
+            return Err(cycle);
+        }
+
+        let closure = self.transitive_closure();
+        let mut reduced = Digraph::new();
+        for node in self.nodes() {
+            reduced.add_node(node.clone());
+        }
+        for (from, to) in self.edges() {
+            // The edge is redundant if another successor already leads to `to`
+            let redundant = self
+                .successors(from)
+                .any(|other| other != to && closure.has_edge(other, to));
+            if !redundant {
+                reduced.add_edge(from.clone(), to.clone());
+            }
+        }
+        Ok(reduced)
+    }
+}
+
+impl<N: Ord + Clone> FromIterator<(N, N)> for Digraph<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(edges: I) -> Self {
+        let mut graph = Digraph::new();
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_topological_sort() {
+        let graph = Digraph::from_iter([(3, 1), (1, 2), (3, 2), (4, 1)]);
+        assert_eq!(graph.topological_sort(), Ok(vec![3, 4, 1, 2]));
+        assert_eq!(graph.find_cycle(), None);
+        // 3 only becomes ready after 2, but still goes before 5
+        let ties = Digraph::from_iter([(1, 5), (2, 3)]);
+        assert_eq!(ties.topological_sort(), Ok(vec![1, 2, 3, 5]));
+
+        let cyclic = Digraph::from_iter([(1, 2), (2, 3), (3, 4), (4, 2)]);
+        assert_eq!(cyclic.topological_sort(), Err(vec![2, 3, 4]));
+        assert_eq!(cyclic.transitive_reduction(), Err(vec![2, 3, 4]));
+    }
+
+    #[test]
+    fn test_induced() {
+        let graph = Digraph::from_iter([(1, 2), (2, 3), (3, 4), (1, 4)]);
+        let induced = graph.induced(|&node| node != 3);
+        assert_eq!(induced, Digraph::from_iter([(1, 2), (1, 4)]));
+    }
+
+    #[test]
+    fn test_closure_and_reduction() {
+        let graph = Digraph::from_iter([(1, 2), (2, 3), (1, 3), (3, 4), (1, 4)]);
+        let closure = graph.transitive_closure();
+        assert_eq!(closure.edge_count(), 6);
+        assert!(closure.has_edge(&2, &4));
+
+        let reduction = graph.transitive_reduction().unwrap();
+        assert_eq!(reduction, Digraph::from_iter([(1, 2), (2, 3), (3, 4)]));
+        assert_eq!(reduction.transitive_closure(), closure);
+    }
+}
@@ -0,0 +1,198 @@
+use std::marker::PhantomData;
+
+use crate::point::Point;
+
+/// A compass of directions ordered clockwise, starting with `Up`.
+pub trait Direction: Copy + Eq + std::fmt::Debug + Sized + 'static {
+    /// Every direction in clockwise order.
+    const ALL: &'static [Self];
+
+    /// The offset of a single step in this direction, with `y` growing downwards.
+    fn unit(self) -> Point;
+
+    fn all() -> impl Iterator<Item = Self> {
+        Self::ALL.iter().copied()
+    }
+
+    /// The position of this direction in [`Direction::ALL`].
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&d| d == self).unwrap()
+    }
+
+    fn turn_right(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    #[allow(dead_code)]
+    fn turn_left(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn opposite(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() / 2) % Self::ALL.len()]
+    }
+
+    fn step(self, point: Point) -> Point {
+        point + self.unit()
+    }
+
+    /// How many right turns it takes to face `other`.
+    #[allow(dead_code)]
+    fn turns_to(self, other: Self) -> Turns<Self> {
+        let count = Self::ALL.len();
+        Turns::clockwise((other.index() + count - self.index()) % count)
+    }
+}
+
+/// The four directions along the axes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dir4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir4 {
+    #[allow(dead_code)]
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, Dir4::Left | Dir4::Right)
+    }
+}
+
+impl Direction for Dir4 {
+    const ALL: &'static [Dir4] = &[Dir4::Up, Dir4::Right, Dir4::Down, Dir4::Left];
+
+    fn unit(self) -> Point {
+        match self {
+            Dir4::Up => Point::new(0, -1),
+            Dir4::Right => Point::new(1, 0),
+            Dir4::Down => Point::new(0, 1),
+            Dir4::Left => Point::new(-1, 0),
+        }
+    }
+}
+
+/// The four axis directions and the four diagonals between them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dir8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction for Dir8 {
+    const ALL: &'static [Dir8] = &[
+        Dir8::Up,
+        Dir8::UpRight,
+        Dir8::Right,
+        Dir8::DownRight,
+        Dir8::Down,
+        Dir8::DownLeft,
+        Dir8::Left,
+        Dir8::UpLeft,
+    ];
+
+    fn unit(self) -> Point {
+        match self {
+            Dir8::Up => Point::new(0, -1),
+            Dir8::UpRight => Point::new(1, -1),
+            Dir8::Right => Point::new(1, 0),
+            Dir8::DownRight => Point::new(1, 1),
+            Dir8::Down => Point::new(0, 1),
+            Dir8::DownLeft => Point::new(-1, 1),
+            Dir8::Left => Point::new(-1, 0),
+            Dir8::UpLeft => Point::new(-1, -1),
+        }
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(dir: Dir4) -> Self {
+        match dir {
+            Dir4::Up => Dir8::Up,
+            Dir4::Right => Dir8::Right,
+            Dir4::Down => Dir8::Down,
+            Dir4::Left => Dir8::Left,
+        }
+    }
+}
+
+/// A number of turns between two directions of the same kind `D`, counted in steps of that
+/// compass: quarter turns for [`Dir4`] and eighth turns for [`Dir8`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(dead_code)]
+pub struct Turns<D> {
+    clockwise: usize,
+    kind: PhantomData<D>,
+}
+
+#[allow(dead_code)]
+impl<D: Direction> Turns<D> {
+    pub fn clockwise(count: usize) -> Turns<D> {
+        Turns {
+            clockwise: count % D::ALL.len(),
+            kind: PhantomData,
+        }
+    }
+
+    pub fn right(self) -> usize {
+        self.clockwise
+    }
+
+    pub fn left(self) -> usize {
+        (D::ALL.len() - self.clockwise) % D::ALL.len()
+    }
+
+    /// The shorter way round, positive for right turns and negative for left turns.
+    pub fn shortest(self) -> i64 {
+        if self.right() <= self.left() {
+            self.right() as i64
+        } else {
+            -(self.left() as i64)
+        }
+    }
+
+    pub fn apply(self, dir: D) -> D {
+        (0..self.clockwise).fold(dir, |dir, _| dir.turn_right())
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Dir4::Up.turn_right(), Dir4::Right);
+        assert_eq!(Dir4::Up.turn_left(), Dir4::Left);
+        assert_eq!(Dir4::Left.opposite(), Dir4::Right);
+        assert_eq!(Dir8::UpLeft.turn_right(), Dir8::Up);
+        assert_eq!(Dir8::DownRight.opposite(), Dir8::UpLeft);
+
+        let turns = Dir4::Up.turns_to(Dir4::Left);
+        assert_eq!((turns.right(), turns.left(), turns.shortest()), (3, 1, -1));
+        assert_eq!(turns.apply(Dir4::Up), Dir4::Left);
+        assert_eq!(Dir8::Up.turns_to(Dir8::DownRight).shortest(), 3);
+    }
+
+    #[test]
+    fn test_units() {
+        for dir in Dir8::all() {
+            assert_eq!(dir.unit() + dir.opposite().unit(), Point::ORIGIN);
+            assert_eq!(dir.unit().chebyshev(Point::ORIGIN), 1);
+        }
+        assert_eq!(
+            Dir4::all()
+                .map(|d| Dir8::from(d).unit())
+                .collect::<Vec<_>>(),
+            Dir4::all().map(Dir4::unit).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use crate::direction::{Dir4, Direction};
+use crate::math;
+use crate::point::Point;
+
+fn cross(a: Point, b: Point) -> i64 {
+    a.x * b.y - a.y * b.x
+}
+
+fn dot(a: Point, b: Point) -> i64 {
+    a.x * b.x + a.y * b.y
+}
+
+/// A straight line between two lattice points, both included. It may run in any direction
+/// or even be a single point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Segment {
+    pub from: Point,
+    pub to: Point,
+}
+
+/// Where two segments meet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Intersection {
+    None,
+    Point(Point),
+    /// The segments are collinear and share more than one point.
+    Overlap(Segment),
+}
+
+impl Segment {
+    pub fn new(from: Point, to: Point) -> Segment {
+        Segment { from, to }
+    }
+
+    #[allow(dead_code)]
+    pub fn is_point(&self) -> bool {
+        self.from == self.to
+    }
+
+    #[allow(dead_code)]
+    pub fn is_horizontal(&self) -> bool {
+        self.from.y == self.to.y
+    }
+
+    #[allow(dead_code)]
+    pub fn is_vertical(&self) -> bool {
+        self.from.x == self.to.x
+    }
+
+    /// The axis direction from `from` to `to`, for segments that are not a single point and
+    /// run along an axis.
+    pub fn direction(&self) -> Option<Dir4> {
+        Dir4::all().find(|dir| dir.unit() == self.step())
+    }
+
+    /// The offset between neighbouring lattice points along the segment, which is the origin
+    /// for a single point.
+    pub fn step(&self) -> Point {
+        let delta = self.to - self.from;
+        match self.steps() {
+            0 => Point::ORIGIN,
+            steps => Point::new(delta.x / steps, delta.y / steps),
+        }
+    }
+
+    /// The number of steps between the lattice points on the segment, which is one less than
+    /// the number of points.
+    pub fn steps(&self) -> i64 {
+        let delta = self.to - self.from;
+        math::gcd(delta.x, delta.y)
+    }
+
+    #[allow(dead_code)]
+    pub fn length_squared(&self) -> i64 {
+        let delta = self.to - self.from;
+        dot(delta, delta)
+    }
+
+    /// The Euclidean length if it is a whole number, as it is for every axis-aligned segment.
+    #[allow(dead_code)]
+    pub fn length(&self) -> Option<i64> {
+        let squared = self.length_squared();
+        let root = squared.isqrt();
+        (root * root == squared).then_some(root)
+    }
+
+    /// Every lattice point from `from` to `to`, both included.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let (from, step) = (self.from, self.step());
+        (0..=self.steps()).map(move |i| from + step * i)
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, point: Point) -> bool {
+        let (a, b) = (point - self.from, point - self.to);
+        cross(a, b) == 0 && dot(a, b) <= 0
+    }
+
+    /// Where this segment meets `other`. Crossings between lattice points, which only occur
+    /// for segments off the axes, count as [`Intersection::None`].
+    #[allow(dead_code)]
+    pub fn intersection(&self, other: &Segment) -> Intersection {
+        if self.is_point() || other.is_point() {
+            let (point, segment) = if self.is_point() {
+                (self.from, other)
+            } else {
+                (other.from, self)
+            };
+            return match segment.contains(point) {
+                true => Intersection::Point(point),
+                false => Intersection::None,
+            };
+        }
+
+        let d1 = self.to - self.from;
+        let d2 = other.to - other.from;
+        let offset = other.from - self.from;
+        let denominator = cross(d1, d2);
+        if denominator == 0 {
+            if cross(offset, d1) != 0 {
+                return Intersection::None;
+            }
+            return self.overlap(other);
+        }
+
+        // self.from + d1 * t == other.from + d2 * u, with both t and u in 0..=1
+        let (mut t, mut u, mut denominator) = (cross(offset, d2), cross(offset, d1), denominator);
+        if denominator < 0 {
+            (t, u, denominator) = (-t, -u, -denominator);
+        }
+        if !(0..=denominator).contains(&t) || !(0..=denominator).contains(&u) {
+            return Intersection::None;
+        }
+        let scaled = d1 * t;
+        if scaled.x % denominator != 0 || scaled.y % denominator != 0 {
+            return Intersection::None;
+        }
+        Intersection::Point(self.from + Point::new(scaled.x / denominator, scaled.y / denominator))
+    }
+
+    /// The shared part of two collinear segments, oriented like `self`.
+    fn overlap(&self, other: &Segment) -> Intersection {
+        let d1 = self.to - self.from;
+        let position = |point: Point| dot(point - self.from, d1);
+        let (mut start, mut end) = (other.from, other.to);
+        if position(start) > position(end) {
+            (start, end) = (end, start);
+        }
+        let start = if position(start) > 0 {
+            start
+        } else {
+            self.from
+        };
+        let end = if position(end) < position(self.to) {
+            end
+        } else {
+            self.to
+        };
+        match position(start).cmp(&position(end)) {
+            std::cmp::Ordering::Greater => Intersection::None,
+            std::cmp::Ordering::Equal => Intersection::Point(start),
+            std::cmp::Ordering::Less => Intersection::Overlap(Segment::new(start, end)),
+        }
+    }
+}
+
+/// Where a point lies relative to a polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// A simple polygon through lattice points, closed from the last vertex back to the first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+#[allow(dead_code)]
+impl Polygon {
+    pub fn new(vertices: Vec<Point>) -> Polygon {
+        Polygon { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Every side, ending with the one back to the first vertex.
+    pub fn edges(&self) -> impl Iterator<Item = Segment> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices
+            .iter()
+            .zip(next)
+            .map(|(&from, &to)| Segment::new(from, to))
+    }
+
+    /// Twice the enclosed area by the shoelace formula, which keeps it a whole number.
+    pub fn double_area(&self) -> i64 {
+        self.edges()
+            .map(|edge| cross(edge.from, edge.to))
+            .sum::<i64>()
+            .abs()
+    }
+
+    /// The number of lattice points on the sides.
+    pub fn boundary_points(&self) -> i64 {
+        self.edges().map(|edge| edge.steps()).sum()
+    }
+
+    /// The number of lattice points strictly inside, by Pick's theorem.
+    pub fn interior_points(&self) -> i64 {
+        (self.double_area() - self.boundary_points() + 2) / 2
+    }
+
+    /// The number of lattice points inside or on the sides, such as the cells a loop dug
+    /// through a grid covers.
+    pub fn lattice_points(&self) -> i64 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    /// Whether `point` is inside, by casting a ray towards growing `x` and counting the sides
+    /// it crosses.
+    pub fn locate(&self, point: Point) -> Location {
+        let mut inside = false;
+        for edge in self.edges() {
+            if edge.contains(point) {
+                return Location::Boundary;
+            }
+            let (a, b) = (edge.from, edge.to);
+            if (a.y > point.y) != (b.y > point.y) {
+                // Whether the side crosses the ray's row to the right of the point
+                let left = (point.x - a.x) * (b.y - a.y);
+                let right = (point.y - a.y) * (b.x - a.x);
+                if (b.y > a.y && left < right) || (b.y < a.y && left > right) {
+                    inside = !inside;
+                }
+            }
+        }
+        match inside {
+            true => Location::Inside,
+            false => Location::Outside,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    fn segment(from: (i64, i64), to: (i64, i64)) -> Segment {
+        Segment::new(from.into(), to.into())
+    }
+
+    #[test]
+    fn test_intersections() {
+        let horizontal = segment((0, 2), (6, 2));
+        assert_eq!(
+            horizontal.intersection(&segment((3, 0), (3, 5))),
+            Intersection::Point(Point::new(3, 2))
+        );
+        assert_eq!(
+            horizontal.intersection(&segment((7, 0), (7, 5))),
+            Intersection::None
+        );
+        assert_eq!(
+            horizontal.intersection(&segment((9, 2), (4, 2))),
+            Intersection::Overlap(segment((4, 2), (6, 2)))
+        );
+        assert_eq!(
+            horizontal.intersection(&segment((6, 2), (8, 2))),
+            Intersection::Point(Point::new(6, 2))
+        );
+        assert_eq!(
+            horizontal.intersection(&segment((0, 3), (6, 3))),
+            Intersection::None
+        );
+        assert_eq!(
+            segment((0, 0), (4, 4)).intersection(&segment((0, 4), (4, 0))),
+            Intersection::Point(Point::new(2, 2))
+        );
+        // Crosses at (0.5, 0.5)
+        assert_eq!(
+            segment((0, 0), (1, 1)).intersection(&segment((0, 1), (1, 0))),
+            Intersection::None
+        );
+    }
+
+    #[test]
+    fn test_segments() {
+        let diagonal = segment((1, 1), (7, 4));
+        assert_eq!((diagonal.steps(), diagonal.step()), (3, Point::new(2, 1)));
+        assert_eq!(diagonal.points().last(), Some(Point::new(7, 4)));
+        assert_eq!(diagonal.direction(), None);
+        assert!(diagonal.contains(Point::new(5, 3)));
+        assert!(!diagonal.contains(Point::new(9, 5)));
+        assert_eq!(diagonal.length(), None);
+        assert_eq!(segment((0, 0), (3, 4)).length(), Some(5));
+
+        let up = segment((2, 5), (2, 1));
+        assert_eq!((up.direction(), up.length()), (Some(Dir4::Up), Some(4)));
+    }
+
+    #[test]
+    fn test_polygons() {
+        // A 4 by 4 square with a 2 by 2 notch cut out of its top right corner
+        let polygon = Polygon::new(
+            [(0, 0), (2, 0), (2, 2), (4, 2), (4, 4), (0, 4)]
+                .map(Point::from)
+                .to_vec(),
+        );
+        assert_eq!(polygon.double_area(), 24);
+        assert_eq!(polygon.boundary_points(), 16);
+        assert_eq!(polygon.interior_points(), 5);
+        assert_eq!(polygon.lattice_points(), 21);
+
+        assert_eq!(polygon.locate(Point::new(1, 1)), Location::Inside);
+        assert_eq!(polygon.locate(Point::new(3, 1)), Location::Outside);
+        assert_eq!(polygon.locate(Point::new(3, 2)), Location::Boundary);
+        assert_eq!(polygon.locate(Point::new(-1, 2)), Location::Outside);
+        assert_eq!(polygon.locate(Point::new(3, 3)), Location::Inside);
+    }
+}
@@ -0,0 +1,373 @@
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+use crate::direction::{Dir4, Dir8, Direction};
+use crate::parse::ParseError;
+use crate::point::Point;
+
+/// A rectangular grid stored row by row in a flat `Vec`, addressed by [`Point`]s.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(cells: Vec<T>, width: usize, height: usize) -> Grid<T> {
+        assert_eq!(cells.len(), width * height);
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// Parses one cell per character, with one row per line. Trailing empty lines are ignored.
+    ///
+    /// Errors returned by `cell` describe what was expected and are reported with the
+    /// position of the offending character.
+    pub fn parse<E: Display>(
+        input: &str,
+        mut cell: impl FnMut(char) -> Result<T, E>,
+    ) -> Result<Grid<T>, ParseError> {
+        let input = input.trim_end_matches(['\n', '\r']);
+        let width = input.lines().next().map_or(0, |line| line.chars().count());
+        if width == 0 {
+            return Err(ParseError::new(0, "", 0, "empty input", "at least one row"));
+        }
+
+        let mut cells = vec![];
+        let mut height = 0;
+        for (idx, line) in input.lines().enumerate() {
+            let mut count = 0;
+            for (column, c) in line.char_indices() {
+                if count == width {
+                    return Err(ParseError::new(
+                        idx,
+                        line,
+                        column,
+                        format!("row has {} cells", line.chars().count()),
+                        format!("{} cells like the first row", width),
+                    ));
+                }
+                let value = cell(c).map_err(|expected| {
+                    ParseError::new(
+                        idx,
+                        line,
+                        column,
+                        format!("unexpected character {:?}", c),
+                        expected.to_string(),
+                    )
+                })?;
+                cells.push(value);
+                count += 1;
+            }
+            if count < width {
+                return Err(ParseError::new(
+                    idx,
+                    line,
+                    line.len(),
+                    format!("row has {} cells", count),
+                    format!("{} cells like the first row", width),
+                ));
+            }
+            height += 1;
+        }
+
+        Ok(Grid::new(cells, width, height))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, point: Point) -> bool {
+        self.index_from(point).is_some()
+    }
+
+    pub fn point_from(&self, index: usize) -> Point {
+        Point::from_index(index, self.width)
+    }
+
+    pub fn index_from(&self, point: Point) -> Option<usize> {
+        point.to_index(self.width, self.height)
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index_from(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index_from(point).map(|index| &mut self.cells[index])
+    }
+
+    /// Walks from `start` in `dir` until leaving the grid.
+    pub fn walk<D: Direction>(&self, start: Point, dir: D) -> Walk<'_, T> {
+        Walk {
+            grid: self,
+            position: start,
+            step: dir.unit(),
+        }
+    }
+
+    pub fn row(&self, y: usize) -> Walk<'_, T> {
+        self.walk(Point::new(0, y as i64), Dir4::Right)
+    }
+
+    pub fn column(&self, x: usize) -> Walk<'_, T> {
+        self.walk(Point::new(x as i64, 0), Dir4::Down)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = Walk<'_, T>> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = Walk<'_, T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Lines running down to the right, starting from the bottom left corner.
+    pub fn diagonals(&self) -> impl Iterator<Item = Walk<'_, T>> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        (0..width + height - 1).map(move |k| {
+            let start = if k < height {
+                Point::new(0, height - 1 - k)
+            } else {
+                Point::new(k - height + 1, 0)
+            };
+            self.walk(start, Dir8::DownRight)
+        })
+    }
+
+    /// Lines running down to the left, starting from the top left corner.
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = Walk<'_, T>> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        (0..width + height - 1).map(move |k| {
+            let start = if k < width {
+                Point::new(k, 0)
+            } else {
+                Point::new(width - 1, k - width + 1)
+            };
+            self.walk(start, Dir8::DownLeft)
+        })
+    }
+
+    /// Every `width` by `height` section of the grid along with its top left corner, in
+    /// reading order. Empty sections have no corner inside the grid, so there are none.
+    pub fn windows(
+        &self,
+        width: usize,
+        height: usize,
+    ) -> impl Iterator<Item = (Point, GridView<'_, T>)> {
+        let empty = width == 0 || height == 0;
+        let xs = if empty {
+            0
+        } else {
+            (self.width + 1).saturating_sub(width)
+        };
+        let ys = if empty {
+            0
+        } else {
+            (self.height + 1).saturating_sub(height)
+        };
+        (0..ys)
+            .flat_map(move |y| (0..xs).map(move |x| Point::new(x as i64, y as i64)))
+            .map(move |origin| {
+                let view = GridView {
+                    grid: self,
+                    origin,
+                    width,
+                    height,
+                };
+                (origin, view)
+            })
+    }
+
+    #[allow(dead_code)]
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Every cell along with its position, in reading order.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Point, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(index, cell)| (Point::from_index(index, width), cell))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{} is outside of the grid", point))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{} is outside of the grid", point))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.width == 0 {
+            return Ok(());
+        }
+        for row in self.cells.chunks(self.width) {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A borrowed rectangular section of a grid, addressed relative to its top left corner.
+#[derive(Clone, Copy, Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    origin: Point,
+    width: usize,
+    height: usize,
+}
+
+impl<'a, T> GridView<'a, T> {
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[allow(dead_code)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, point: Point) -> Option<&'a T> {
+        point
+            .to_index(self.width, self.height)
+            .and_then(|_| self.grid.get(self.origin + point))
+    }
+}
+
+impl<T> Index<Point> for GridView<'_, T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{} is outside of the view", point))
+    }
+}
+
+/// The cells along a straight line through a grid, see [`Grid::walk`].
+#[derive(Clone, Debug)]
+pub struct Walk<'a, T> {
+    grid: &'a Grid<T>,
+    position: Point,
+    step: Point,
+}
+
+impl<'a, T> Iterator for Walk<'a, T> {
+    type Item = (Point, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cell = self.grid.get(self.position)?;
+        let position = self.position;
+        self.position += self.step;
+        Some((position, cell))
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_access() {
+        let grid = Grid::parse("abc\ndef\n", Ok::<char, String>).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point::new(2, 0)], 'c');
+        assert_eq!(grid.get(Point::new(1, 1)), Some(&'e'));
+        assert_eq!(grid.get(Point::new(3, 1)), None);
+        assert_eq!(grid.get(Point::new(0, -1)), None);
+        assert_eq!(grid.row(1).map(|(_, c)| c).collect::<String>(), "def");
+        assert_eq!(grid.column(1).map(|(_, c)| c).collect::<String>(), "be");
+        assert_eq!(grid.to_string(), "abc\ndef\n");
+    }
+
+    #[test]
+    fn test_lines() {
+        let grid = Grid::parse("abc\ndef\n", Ok::<char, String>).unwrap();
+        let lines = |lines: Vec<Walk<'_, char>>| {
+            lines
+                .into_iter()
+                .map(|line| line.map(|(_, c)| c).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(lines(grid.rows().collect()), ["abc", "def"]);
+        assert_eq!(lines(grid.columns().collect()), ["ad", "be", "cf"]);
+        assert_eq!(lines(grid.diagonals().collect()), ["d", "ae", "bf", "c"]);
+        assert_eq!(
+            lines(grid.anti_diagonals().collect()),
+            ["a", "bd", "ce", "f"]
+        );
+        assert_eq!(
+            grid.anti_diagonals().nth(2).unwrap().collect::<Vec<_>>(),
+            [(Point::new(2, 0), &'c'), (Point::new(1, 1), &'e')]
+        );
+    }
+
+    #[test]
+    fn test_windows() {
+        let grid = Grid::parse("abcd\nefgh\nijkl\n", Ok::<char, String>).unwrap();
+        let windows = grid.windows(3, 2).collect::<Vec<_>>();
+        assert_eq!(
+            windows
+                .iter()
+                .map(|(origin, _)| *origin)
+                .collect::<Vec<_>>(),
+            [
+                Point::new(0, 0),
+                Point::new(1, 0),
+                Point::new(0, 1),
+                Point::new(1, 1)
+            ]
+        );
+
+        let (_, view) = windows[3];
+        assert_eq!((view.width(), view.height()), (3, 2));
+        assert_eq!(view[Point::new(0, 0)], 'f');
+        assert_eq!(view[Point::new(2, 1)], 'l');
+        assert_eq!(view.get(Point::new(3, 0)), None);
+        assert_eq!(grid.windows(5, 1).count(), 0);
+        assert_eq!(grid.windows(0, 1).count(), 0);
+        assert_eq!(grid.windows(2, 0).count(), 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let digit = |c: char| c.to_digit(10).ok_or("a digit");
+
+        let error = Grid::parse("123\n4x6\n", digit).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 2));
+
+        let error = Grid::parse("123\n4567\n", digit).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 4));
+
+        let error = Grid::parse("123\n45\n", digit).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 3));
+    }
+}
@@ -0,0 +1,10 @@
+//! Puzzle inputs compiled into the binary by the `embed-inputs` feature.
+
+// Generated by build.rs from the files in inputs/
+include!(concat!(env!("OUT_DIR"), "/inputs.rs"));
+
+pub fn embedded(day: usize) -> Option<&'static str> {
+    INPUTS
+        .iter()
+        .find_map(|&(embedded_day, input)| (embedded_day == day).then_some(input))
+}
@@ -0,0 +1,674 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub};
+
+use crate::math::{self, MathError};
+
+/// An exact fraction kept in lowest terms with a positive denominator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    /// Panics if `den` is zero or the reduced fraction does not fit.
+    pub fn new(num: i128, den: i128) -> Rational {
+        Rational::checked_new(num, den)
+            .unwrap_or_else(|| panic!("invalid rational {}/{}", num, den))
+    }
+
+    pub fn checked_new(num: i128, den: i128) -> Option<Rational> {
+        if den == 0 {
+            return None;
+        }
+        let divisor = math::checked_gcd(num, den).ok()?;
+        let (num, den) = (num / divisor, den / divisor);
+        if den < 0 {
+            Some(Rational {
+                num: num.checked_neg()?,
+                den: den.checked_neg()?,
+            })
+        } else {
+            Some(Rational { num, den })
+        }
+    }
+
+    pub fn numerator(self) -> i128 {
+        self.num
+    }
+
+    pub fn denominator(self) -> i128 {
+        self.den
+    }
+
+    pub fn is_integer(self) -> bool {
+        self.den == 1
+    }
+
+    /// The value as an integer, if it is one.
+    pub fn to_integer(self) -> Option<i128> {
+        self.is_integer().then_some(self.num)
+    }
+
+    pub fn checked_add(self, other: Rational) -> Option<Rational> {
+        let num = self
+            .num
+            .checked_mul(other.den)?
+            .checked_add(other.num.checked_mul(self.den)?)?;
+        Rational::checked_new(num, self.den.checked_mul(other.den)?)
+    }
+
+    pub fn checked_sub(self, other: Rational) -> Option<Rational> {
+        self.checked_add(Rational {
+            num: other.num.checked_neg()?,
+            den: other.den,
+        })
+    }
+
+    pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+        // Cancel crosswise first to keep the intermediate products small
+        let a = math::checked_gcd(self.num, other.den).ok()?.max(1);
+        let b = math::checked_gcd(other.num, self.den).ok()?.max(1);
+        let num = (self.num / a).checked_mul(other.num / b)?;
+        let den = (self.den / b).checked_mul(other.den / a)?;
+        Rational::checked_new(num, den)
+    }
+
+    pub fn checked_div(self, other: Rational) -> Option<Rational> {
+        if other.num == 0 {
+            return None;
+        }
+        self.checked_mul(Rational::checked_new(other.den, other.num)?)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Rational {
+            num: value as i128,
+            den: 1,
+        }
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl Ord for Rational {
+    /// Compares by continued fractions, since cross-multiplying may overflow even an i128.
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Compares a / b with c / d, where b and d stay positive
+        let (mut a, mut b, mut c, mut d) = (self.num, self.den, other.num, other.den);
+        loop {
+            let (whole, rest) = (a.div_euclid(b), a.rem_euclid(b));
+            let (other_whole, other_rest) = (c.div_euclid(d), c.rem_euclid(d));
+            if whole != other_whole {
+                return whole.cmp(&other_whole);
+            }
+            match (rest, other_rest) {
+                (0, 0) => return Ordering::Equal,
+                (0, _) => return Ordering::Less,
+                (_, 0) => return Ordering::Greater,
+                // rest / b < other_rest / d exactly when d / other_rest < b / rest
+                _ => (a, b, c, d) = (d, other_rest, b, rest),
+            }
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+macro_rules! rational_op {
+    ($trait:ident, $method:ident, $checked:ident) => {
+        impl $trait for Rational {
+            type Output = Rational;
+
+            fn $method(self, other: Rational) -> Rational {
+                self.$checked(other).unwrap_or_else(|| {
+                    panic!(
+                        "{} {} {} is not representable",
+                        self,
+                        stringify!($method),
+                        other
+                    )
+                })
+            }
+        }
+    };
+}
+
+rational_op!(Add, add, checked_add);
+rational_op!(Sub, sub, checked_sub);
+rational_op!(Mul, mul, checked_mul);
+rational_op!(Div, div, checked_div);
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational::ZERO - self
+    }
+}
+
+/// The exact number types matrices can hold. Every operation is checked, and division is
+/// only defined when the result is exact.
+pub trait Scalar: Copy + Eq + Debug + Display {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_div(self, other: Self) -> Option<Self>;
+}
+
+impl Scalar for i64 {
+    const ZERO: i64 = 0;
+    const ONE: i64 = 1;
+
+    fn checked_add(self, other: i64) -> Option<i64> {
+        i64::checked_add(self, other)
+    }
+
+    fn checked_sub(self, other: i64) -> Option<i64> {
+        i64::checked_sub(self, other)
+    }
+
+    fn checked_mul(self, other: i64) -> Option<i64> {
+        i64::checked_mul(self, other)
+    }
+
+    fn checked_div(self, other: i64) -> Option<i64> {
+        (other != 0 && self % other == 0).then(|| self / other)
+    }
+}
+
+impl Scalar for Rational {
+    const ZERO: Rational = Rational::ZERO;
+    const ONE: Rational = Rational::ONE;
+
+    fn checked_add(self, other: Rational) -> Option<Rational> {
+        Rational::checked_add(self, other)
+    }
+
+    fn checked_sub(self, other: Rational) -> Option<Rational> {
+        Rational::checked_sub(self, other)
+    }
+
+    fn checked_mul(self, other: Rational) -> Option<Rational> {
+        Rational::checked_mul(self, other)
+    }
+
+    fn checked_div(self, other: Rational) -> Option<Rational> {
+        Rational::checked_div(self, other)
+    }
+}
+
+fn overflow<T>(value: Option<T>) -> Result<T, MathError> {
+    value.ok_or(MathError::Overflow)
+}
+
+/// A column vector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vector<T> {
+    values: Vec<T>,
+}
+
+impl<T: Scalar> Vector<T> {
+    pub fn new(values: Vec<T>) -> Vector<T> {
+        Vector { values }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.values.iter()
+    }
+
+    pub fn checked_dot(&self, other: &Vector<T>) -> Result<T, MathError> {
+        assert_eq!(self.len(), other.len(), "vectors differ in length");
+        self.iter()
+            .zip(other.iter())
+            .try_fold(T::ZERO, |sum, (&a, &b)| {
+                overflow(
+                    a.checked_mul(b)
+                        .and_then(|product| sum.checked_add(product)),
+                )
+            })
+    }
+}
+
+impl<T> Index<usize> for Vector<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.values[index]
+    }
+}
+
+impl<T: Scalar> From<Vec<T>> for Vector<T> {
+    fn from(values: Vec<T>) -> Self {
+        Vector::new(values)
+    }
+}
+
+/// What a system of linear equations `A x = b` allows for `x`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Solutions {
+    None,
+    Unique(Vector<Rational>),
+    /// Every solution is `particular` with the `free` variables set to anything and the
+    /// others adjusted to match. `particular` has all free variables set to zero.
+    Infinite {
+        particular: Vector<Rational>,
+        free: Vec<usize>,
+    },
+}
+
+/// A dense matrix stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matrix<T> {
+    cells: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T: Scalar> Matrix<T> {
+    pub fn new(cells: Vec<T>, rows: usize, cols: usize) -> Matrix<T> {
+        assert_eq!(cells.len(), rows * cols);
+        Matrix { cells, rows, cols }
+    }
+
+    /// Panics if the rows differ in length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Matrix<T> {
+        let cols = rows.first().map_or(0, Vec::len);
+        assert!(rows.iter().all(|row| row.len() == cols), "ragged rows");
+        let count = rows.len();
+        Matrix::new(rows.into_iter().flatten().collect(), count, cols)
+    }
+
+    pub fn zero(rows: usize, cols: usize) -> Matrix<T> {
+        Matrix::new(vec![T::ZERO; rows * cols], rows, cols)
+    }
+
+    pub fn identity(size: usize) -> Matrix<T> {
+        let mut identity = Matrix::zero(size, size);
+        for i in 0..size {
+            identity[(i, i)] = T::ONE;
+        }
+        identity
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn transpose(&self) -> Matrix<T> {
+        let cells = (0..self.cols)
+            .flat_map(|col| (0..self.rows).map(move |row| self[(row, col)]))
+            .collect();
+        Matrix::new(cells, self.cols, self.rows)
+    }
+
+    pub fn map<U: Scalar>(&self, f: impl FnMut(T) -> U) -> Matrix<U> {
+        Matrix::new(
+            self.cells.iter().copied().map(f).collect(),
+            self.rows,
+            self.cols,
+        )
+    }
+
+    pub fn checked_mul(&self, other: &Matrix<T>) -> Result<Matrix<T>, MathError> {
+        assert_eq!(self.cols, other.rows, "matrix dimensions do not match");
+        let mut product = Matrix::zero(self.rows, other.cols);
+        for row in 0..self.rows {
+            for col in 0..other.cols {
+                let mut sum = T::ZERO;
+                for k in 0..self.cols {
+                    let term = overflow(self[(row, k)].checked_mul(other[(k, col)]))?;
+                    sum = overflow(sum.checked_add(term))?;
+                }
+                product[(row, col)] = sum;
+            }
+        }
+        Ok(product)
+    }
+
+    pub fn checked_mul_vector(&self, vector: &Vector<T>) -> Result<Vector<T>, MathError> {
+        let column = Matrix::new(vector.values.clone(), vector.len(), 1);
+        Ok(Vector::new(self.checked_mul(&column)?.cells))
+    }
+
+    /// The matrix to the power of `exponent` by repeated squaring, as used to jump ahead
+    /// in linear recurrences.
+    pub fn checked_pow(&self, exponent: u64) -> Result<Matrix<T>, MathError> {
+        self.pow_by(exponent, |a, b| a.checked_mul(b))
+    }
+
+    /// The determinant by fraction-free elimination (Bareiss), which stays exact on integers.
+    pub fn checked_determinant(&self) -> Result<T, MathError> {
+        assert_eq!(self.rows, self.cols, "determinant of a non-square matrix");
+        let n = self.rows;
+        let mut m = self.clone();
+        let mut sign_flipped = false;
+        let mut previous = T::ONE;
+        for k in 0..n {
+            let Some(pivot) = (k..n).find(|&row| m[(row, k)] != T::ZERO) else {
+                return Ok(T::ZERO);
+            };
+            if pivot != k {
+                m.swap_rows(pivot, k);
+                sign_flipped = !sign_flipped;
+            }
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    let a = overflow(m[(i, j)].checked_mul(m[(k, k)]))?;
+                    let b = overflow(m[(i, k)].checked_mul(m[(k, j)]))?;
+                    // Bareiss guarantees this division is exact
+                    m[(i, j)] = overflow(a.checked_sub(b).and_then(|d| d.checked_div(previous)))?;
+                }
+            }
+            previous = m[(k, k)];
+        }
+        let determinant = if n == 0 { T::ONE } else { m[(n - 1, n - 1)] };
+        if sign_flipped {
+            overflow(T::ZERO.checked_sub(determinant))
+        } else {
+            Ok(determinant)
+        }
+    }
+
+    /// The reduced row echelon form over the rationals, along with the pivot column of each
+    /// non-zero row.
+    pub fn reduced_row_echelon(&self) -> Result<(Matrix<Rational>, Vec<usize>), MathError>
+    where
+        Rational: From<T>,
+    {
+        let mut m = self.map(Rational::from);
+        let mut pivots = vec![];
+        for col in 0..m.cols {
+            let row = pivots.len();
+            let Some(pivot) = (row..m.rows).find(|&r| m[(r, col)] != Rational::ZERO) else {
+                continue;
+            };
+            m.swap_rows(pivot, row);
+            let scale = m[(row, col)];
+            for j in 0..m.cols {
+                m[(row, j)] = overflow(m[(row, j)].checked_div(scale))?;
+            }
+            for other in 0..m.rows {
+                let factor = m[(other, col)];
+                if other == row || factor == Rational::ZERO {
+                    continue;
+                }
+                for j in 0..m.cols {
+                    let term = overflow(factor.checked_mul(m[(row, j)]))?;
+                    m[(other, j)] = overflow(m[(other, j)].checked_sub(term))?;
+                }
+            }
+            pivots.push(col);
+        }
+        Ok((m, pivots))
+    }
+
+    pub fn rank(&self) -> Result<usize, MathError>
+    where
+        Rational: From<T>,
+    {
+        Ok(self.reduced_row_echelon()?.1.len())
+    }
+
+    /// Solves `self * x = b` exactly.
+    pub fn solve(&self, b: &Vector<T>) -> Result<Solutions, MathError>
+    where
+        Rational: From<T>,
+    {
+        assert_eq!(
+            self.rows,
+            b.len(),
+            "right hand side does not match the rows"
+        );
+        let mut augmented = Matrix::zero(self.rows, self.cols + 1);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                augmented[(row, col)] = self[(row, col)];
+            }
+            augmented[(row, self.cols)] = b[row];
+        }
+
+        let (reduced, pivots) = augmented.reduced_row_echelon()?;
+        if pivots.last() == Some(&self.cols) {
+            // A row reads 0 = 1
+            return Ok(Solutions::None);
+        }
+        let mut particular = vec![Rational::ZERO; self.cols];
+        for (row, &col) in pivots.iter().enumerate() {
+            particular[col] = reduced[(row, self.cols)];
+        }
+        let particular = Vector::new(particular);
+        if pivots.len() == self.cols {
+            Ok(Solutions::Unique(particular))
+        } else {
+            let free = (0..self.cols).filter(|col| !pivots.contains(col)).collect();
+            Ok(Solutions::Infinite { particular, free })
+        }
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        if a != b {
+            for col in 0..self.cols {
+                self.cells.swap(a * self.cols + col, b * self.cols + col);
+            }
+        }
+    }
+
+    fn pow_by(
+        &self,
+        mut exponent: u64,
+        mut mul: impl FnMut(&Matrix<T>, &Matrix<T>) -> Result<Matrix<T>, MathError>,
+    ) -> Result<Matrix<T>, MathError> {
+        assert_eq!(self.rows, self.cols, "power of a non-square matrix");
+        let mut result = Matrix::identity(self.rows);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = mul(&result, &base)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = mul(&base, &base)?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Matrix<i64> {
+    /// The matrix to the power of `exponent` with every entry reduced into `0..modulus`.
+    pub fn pow_mod(&self, exponent: u64, modulus: i64) -> Result<Matrix<i64>, MathError> {
+        if modulus <= 0 {
+            return Err(MathError::InvalidModulus);
+        }
+        let reduced = self.map(|value| value.rem_euclid(modulus));
+        reduced
+            .pow_by(exponent, |a, b| {
+                let mut product = Matrix::zero(a.rows, b.cols);
+                for row in 0..a.rows {
+                    for col in 0..b.cols {
+                        let sum = (0..a.cols)
+                            .map(|k| a[(row, k)] as i128 * b[(k, col)] as i128 % modulus as i128)
+                            .sum::<i128>();
+                        product[(row, col)] = (sum % modulus as i128) as i64;
+                    }
+                }
+                Ok(product)
+            })
+            .map(|result| result.map(|value| value % modulus))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    /// Indexed by `(row, column)`.
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "({}, {}) is outside of the matrix",
+            row,
+            col
+        );
+        &self.cells[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "({}, {}) is outside of the matrix",
+            row,
+            col
+        );
+        &mut self.cells[row * self.cols + col]
+    }
+}
+
+impl<T: Scalar> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    /// Panics on overflow, see [`Matrix::checked_mul`].
+    fn mul(self, other: &Matrix<T>) -> Matrix<T> {
+        self.checked_mul(other)
+            .unwrap_or_else(|error| panic!("matrix product: {}", error))
+    }
+}
+
+impl<T: Scalar> Display for Matrix<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.rows {
+            let cells = self.row(row).iter().map(T::to_string).collect::<Vec<_>>();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    fn rational(num: i128, den: i128) -> Rational {
+        Rational::new(num, den)
+    }
+
+    #[test]
+    fn test_rationals() {
+        assert_eq!(rational(6, -4), rational(-3, 2));
+        assert_eq!(rational(1, 3) + rational(1, 6), rational(1, 2));
+        assert_eq!(rational(2, 3) / rational(4, 9), rational(3, 2));
+        assert!(rational(1, 3) < rational(1, 2));
+        assert_eq!(rational(-3, 2).to_string(), "-3/2");
+        assert_eq!(Rational::from(4).to_integer(), Some(4));
+        assert_eq!(Rational::checked_new(1, 0), None);
+        assert_eq!(rational(1, 2).checked_div(Rational::ZERO), None);
+
+        // Cross-multiplying these overflows an i128
+        assert!(rational(i128::MAX, 2) > rational(i128::MAX, 3));
+        assert!(rational(-i128::MAX, 3) < rational(-i128::MAX + 2, 3));
+        assert!(rational(i128::MAX - 1, i128::MAX) > rational(i128::MAX - 2, i128::MAX - 1));
+        assert_eq!(rational(-7, 3).cmp(&rational(-14, 6)), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_determinant_and_pow() {
+        let m = Matrix::from_rows(vec![vec![2i64, -3, 1], vec![2, 0, -1], vec![1, 4, 5]]);
+        assert_eq!(m.checked_determinant(), Ok(49));
+        assert_eq!(m.transpose().checked_determinant(), Ok(49));
+        let swapped = Matrix::from_rows(vec![vec![0i64, 1], vec![1, 0]]);
+        assert_eq!(swapped.checked_determinant(), Ok(-1));
+        assert_eq!(
+            m.map(Rational::from).checked_determinant(),
+            Ok(Rational::from(49))
+        );
+
+        let fibonacci = Matrix::from_rows(vec![vec![1i64, 1], vec![1, 0]]);
+        assert_eq!(
+            fibonacci.checked_pow(90).unwrap()[(0, 1)],
+            2880067194370816120
+        );
+        assert_eq!(fibonacci.checked_pow(100), Err(MathError::Overflow));
+        assert_eq!(
+            fibonacci.pow_mod(100, 1_000_000_007).unwrap()[(0, 1)],
+            687995182
+        );
+        assert_eq!(fibonacci.checked_pow(0), Ok(Matrix::identity(2)));
+    }
+
+    #[test]
+    fn test_solve() {
+        // 94a + 22b = 8400, 34a + 67b = 5400
+        let a = Matrix::from_rows(vec![vec![94i64, 22], vec![34, 67]]);
+        let b = Vector::new(vec![8400, 5400]);
+        let expected = Vector::new(vec![Rational::from(80), Rational::from(40)]);
+        assert_eq!(a.solve(&b), Ok(Solutions::Unique(expected)));
+
+        let singular = Matrix::from_rows(vec![vec![1i64, 2], vec![2, 4]]);
+        assert_eq!(singular.rank(), Ok(1));
+        assert_eq!(
+            singular.solve(&Vector::new(vec![1, 3])),
+            Ok(Solutions::None)
+        );
+        assert_eq!(
+            singular.solve(&Vector::new(vec![1, 2])),
+            Ok(Solutions::Infinite {
+                particular: Vector::new(vec![Rational::ONE, Rational::ZERO]),
+                free: vec![1],
+            })
+        );
+
+        let halves = Matrix::from_rows(vec![vec![2i64]]).solve(&Vector::new(vec![1]));
+        assert_eq!(
+            halves,
+            Ok(Solutions::Unique(Vector::new(vec![rational(1, 2)])))
+        );
+
+        let max = i64::MAX;
+        let huge = Matrix::from_rows(vec![
+            vec![max, max - 1, 1],
+            vec![max - 2, max, 3],
+            vec![1, max - 5, max],
+        ]);
+        assert_eq!(huge.rank(), Err(MathError::Overflow));
+    }
+}
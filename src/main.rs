@@ -1,52 +1,218 @@
+use std::borrow::Cow;
+use std::time::Instant;
+
 use time::{Duration, OffsetDateTime};
 
+use crate::parse::ParseError;
+use crate::rng::Rng;
+
+mod batch;
+mod bit_grid;
+mod bytes;
+mod counter;
+mod cycle;
 mod day01;
 mod day02;
 mod day03;
 mod day04;
 mod day05;
 mod day06;
+mod digraph;
+mod direction;
+mod geometry;
+mod grid;
+#[cfg(feature = "embed-inputs")]
+mod inputs;
+mod linalg;
+mod math;
+mod memo;
+mod parse;
+mod point;
+mod queue;
+#[allow(dead_code)] // no day merges integer ranges yet
+mod range_set;
+mod rng;
+mod scale;
+mod search;
+#[allow(dead_code)] // no day needs a grid without fixed bounds yet
+mod sparse_grid;
+#[allow(dead_code)] // no day groups connected cells yet
+mod union_find;
 
 fn main() {
     let days = [
-        day01::Day::print,
-        day02::Day::print,
-        day03::Day::print,
-        day04::Day::print,
-        day05::Day::print,
-        day06::Day::print,
+        Runner::of::<day01::Day>(),
+        Runner::of::<day02::Day>(),
+        Runner::of::<day03::Day>(),
+        Runner::of::<day04::Day>(),
+        Runner::of::<day05::Day>(),
+        Runner::of::<day06::Day>(),
     ];
 
-    let today = (OffsetDateTime::now_utc() - Duration::hours(6)).day() as usize;
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        ["batch", dir] => {
+            if !batch::run(dir, &days) {
+                std::process::exit(1);
+            }
+        }
+        ["scale", day, rest @ ..] => {
+            let day = day
+                .parse::<usize>()
+                .ok()
+                .filter(|d| (1..=days.len()).contains(d));
+            let max_size = rest.first().map_or(Some(256), |max| max.parse().ok());
+            match (day, max_size) {
+                (Some(day), Some(max_size)) => (days[day - 1].scale)(max_size),
+                _ => eprintln!("Usage: aoc2024 scale <1-{}> [max size]", days.len()),
+            }
+        }
+        ["queues", rest @ ..] => match rest.first().map_or(Some(512), |size| size.parse().ok()) {
+            Some(size) => queue::bench(size),
+            None => eprintln!("Usage: aoc2024 queues [grid size]"),
+        },
+        [] => {
+            let today = (OffsetDateTime::now_utc() - Duration::hours(6)).day() as usize;
+
+            if today <= days.len() {
+                println!("Outputting Day {}", today);
+                (days[today - 1].print)();
+            } else {
+                println!("Outputting All Days");
+                for day in days {
+                    (day.print)();
+                }
+            }
+        }
+        _ => {
+            eprintln!("Usage: aoc2024 [scale <day> [max size] | batch <dir> | queues [grid size]]")
+        }
+    }
+}
+
+struct Runner {
+    day: usize,
+    print: fn(),
+    solve: fn(&[u8]) -> Result<Solution, ParseError>,
+    scale: fn(usize),
+}
 
-    if today <= days.len() {
-        println!("Outputting Day {}", today);
-        days[today - 1]();
-    } else {
-        println!("Outputting All Days");
-        for day in days {
-            day();
+impl Runner {
+    fn of<D: AdventOfCodeDay>() -> Runner {
+        Runner {
+            day: D::DAY,
+            print: D::print,
+            solve: D::solve,
+            scale: scale::run::<D>,
         }
     }
 }
 
+/// Answers of both parts along with how long parsing and each part took.
+#[derive(Clone, Debug)]
+struct Solution {
+    part_1: i64,
+    part_2: i64,
+    parse_time: std::time::Duration,
+    part_1_time: std::time::Duration,
+    part_2_time: std::time::Duration,
+}
+
+impl Solution {
+    fn total_time(&self) -> std::time::Duration {
+        self.parse_time + self.part_1_time + self.part_2_time
+    }
+}
+
 trait AdventOfCodeDay {
     const DAY: usize;
     type Parsed: Clone;
 
-    fn parse(input: String) -> Self::Parsed;
+    fn parse(input: String) -> Result<Self::Parsed, ParseError>;
+
+    /// Parses the raw input. Days scanning it with [`bytes`] override this and have `parse`
+    /// call it, the others get their input as a `String` once it is checked to be UTF-8.
+    fn parse_bytes(input: &[u8]) -> Result<Self::Parsed, ParseError> {
+        match std::str::from_utf8(input) {
+            Ok(input) => Self::parse(input.to_string()),
+            Err(error) => Err(bytes::invalid_utf8(input, error)),
+        }
+    }
+
     fn part_1(parsed: Self::Parsed) -> i64;
     fn part_2(parsed: Self::Parsed) -> i64;
 
-    fn load() -> String {
-        std::fs::read_to_string(format!("inputs/{:0>2}.txt", Self::DAY.to_string())).unwrap()
+    /// Generates a random input whose size grows with `size`, used by the `scale` command.
+    fn generate(_size: usize, _rng: &mut Rng) -> Option<String> {
+        None
     }
+
+    fn load() -> Cow<'static, [u8]> {
+        #[cfg(feature = "embed-inputs")]
+        if let Some(input) = inputs::embedded(Self::DAY) {
+            return Cow::Borrowed(input.as_bytes());
+        }
+        Cow::Owned(std::fs::read(format!("inputs/{:0>2}.txt", Self::DAY.to_string())).unwrap())
+    }
+    fn solve(input: &[u8]) -> Result<Solution, ParseError> {
+        let start = Instant::now();
+        let parsed = Self::parse_bytes(input)?;
+        let parse_time = start.elapsed();
+
+        let cloned = parsed.clone();
+        let start = Instant::now();
+        let part_1 = Self::part_1(cloned);
+        let part_1_time = start.elapsed();
+
+        let start = Instant::now();
+        let part_2 = Self::part_2(parsed);
+        let part_2_time = start.elapsed();
+
+        Ok(Solution {
+            part_1,
+            part_2,
+            parse_time,
+            part_1_time,
+            part_2_time,
+        })
+    }
+
     fn print() {
         let input = Self::load();
-        let parsed = Self::parse(input);
-        let res1 = Self::part_1(parsed.clone());
-        println!("Result Day {:02} Part 1: {}", Self::DAY, res1);
+        let parsed = match Self::parse_bytes(&input) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                eprintln!("Failed to parse input for Day {:02}", Self::DAY);
+                eprint!("{}", error);
+                return;
+            }
+        };
+        let cloned = parsed.clone();
+        let start = Instant::now();
+        let res1 = Self::part_1(cloned);
+        println!(
+            "Result Day {:02} Part 1: {} ({:.3?})",
+            Self::DAY,
+            res1,
+            start.elapsed()
+        );
+        print_memo_stats();
+        let start = Instant::now();
         let res2 = Self::part_2(parsed);
-        println!("Result Day {:02} Part 2: {}", Self::DAY, res2);
+        println!(
+            "Result Day {:02} Part 2: {} ({:.3?})",
+            Self::DAY,
+            res2,
+            start.elapsed()
+        );
+        print_memo_stats();
+    }
+}
+
+/// Lists the caches used since the last call below the timing of a part or a day.
+fn print_memo_stats() {
+    for (name, stats) in memo::take_reports() {
+        println!("    memo {}: {}", name, stats);
     }
 }
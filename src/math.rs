@@ -0,0 +1,355 @@
+use std::fmt::{Debug, Display};
+
+/// Why a number theory operation has no representable answer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    /// The result does not fit in the integer type.
+    Overflow,
+    /// A modulus was zero or negative.
+    InvalidModulus,
+    /// The value shares a factor with the modulus, so it has no inverse.
+    NotInvertible,
+    /// The congruences contradict each other.
+    NoSolution,
+}
+
+impl Display for MathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            MathError::Overflow => "arithmetic overflow",
+            MathError::InvalidModulus => "modulus must be positive",
+            MathError::NotInvertible => "value is not invertible modulo the modulus",
+            MathError::NoSolution => "congruences have no common solution",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for MathError {}
+
+/// The integer types the functions in this module work on. Intermediate results are computed
+/// on `u128`, which holds the magnitude of every supported value.
+pub trait Integer: Copy + Ord + Debug + Display {
+    #[allow(dead_code)]
+    const ZERO: Self;
+    #[allow(dead_code)]
+    const ONE: Self;
+
+    #[allow(dead_code)]
+    fn checked_add(self, other: Self) -> Option<Self>;
+    #[allow(dead_code)]
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    #[allow(dead_code)]
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    #[allow(dead_code)]
+    fn checked_div(self, other: Self) -> Option<Self>;
+    fn unsigned_abs(self) -> u128;
+    fn from_u128(value: u128) -> Option<Self>;
+
+    #[allow(dead_code)]
+    fn is_negative(self) -> bool {
+        self < Self::ZERO
+    }
+}
+
+/// Integer types that can hold negative numbers, like Bézout coefficients.
+pub trait Signed: Integer {}
+
+macro_rules! integer {
+    ($($t:ty),*) => {$(
+        impl Integer for $t {
+            const ZERO: $t = 0;
+            const ONE: $t = 1;
+
+            fn checked_add(self, other: $t) -> Option<$t> {
+                <$t>::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: $t) -> Option<$t> {
+                <$t>::checked_sub(self, other)
+            }
+
+            fn checked_mul(self, other: $t) -> Option<$t> {
+                <$t>::checked_mul(self, other)
+            }
+
+            fn checked_div(self, other: $t) -> Option<$t> {
+                <$t>::checked_div(self, other)
+            }
+
+            fn unsigned_abs(self) -> u128 {
+                #[allow(unused_comparisons)]
+                if self < 0 {
+                    // Wrapping keeps i128::MIN, whose bits are exactly 2^127
+                    (self as i128).wrapping_neg() as u128
+                } else {
+                    self as u128
+                }
+            }
+
+            fn from_u128(value: u128) -> Option<$t> {
+                <$t>::try_from(value).ok()
+            }
+        }
+    )*};
+}
+
+integer!(i64, i128, u64);
+
+impl Signed for i64 {}
+impl Signed for i128 {}
+
+/// Adds up `values`, failing instead of wrapping around.
+#[allow(dead_code)]
+pub fn checked_sum<T: Integer>(values: impl IntoIterator<Item = T>) -> Result<T, MathError> {
+    values.into_iter().try_fold(T::ZERO, |sum, value| {
+        sum.checked_add(value).ok_or(MathError::Overflow)
+    })
+}
+
+/// Multiplies `values`, failing instead of wrapping around.
+#[allow(dead_code)]
+pub fn checked_product<T: Integer>(values: impl IntoIterator<Item = T>) -> Result<T, MathError> {
+    values.into_iter().try_fold(T::ONE, |product, value| {
+        product.checked_mul(value).ok_or(MathError::Overflow)
+    })
+}
+
+/// The greatest common divisor, which is never negative. Panics if it does not fit in `T`.
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    checked_gcd(a, b).unwrap_or_else(|error| panic!("gcd({}, {}): {}", a, b, error))
+}
+
+/// Like [`gcd`], but fails for `gcd(T::MIN, 0)` and friends instead of panicking.
+pub fn checked_gcd<T: Integer>(a: T, b: T) -> Result<T, MathError> {
+    T::from_u128(gcd_u128(a.unsigned_abs(), b.unsigned_abs())).ok_or(MathError::Overflow)
+}
+
+/// The least common multiple, which is never negative. Panics if it does not fit in `T`.
+#[allow(dead_code)]
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    checked_lcm(a, b).unwrap_or_else(|error| panic!("lcm({}, {}): {}", a, b, error))
+}
+
+#[allow(dead_code)]
+pub fn checked_lcm<T: Integer>(a: T, b: T) -> Result<T, MathError> {
+    let lcm = lcm_u128(a.unsigned_abs(), b.unsigned_abs()).ok_or(MathError::Overflow)?;
+    T::from_u128(lcm).ok_or(MathError::Overflow)
+}
+
+/// Returns `(g, x, y)` with `a * x + b * y == g`, where `g` is the non-negative gcd.
+/// Panics if any of them do not fit in `T`.
+#[allow(dead_code)]
+pub fn extended_gcd<T: Signed>(a: T, b: T) -> (T, T, T) {
+    checked_extended_gcd(a, b)
+        .unwrap_or_else(|error| panic!("extended_gcd({}, {}): {}", a, b, error))
+}
+
+#[allow(dead_code)]
+pub fn checked_extended_gcd<T: Signed>(a: T, b: T) -> Result<(T, T, T), MathError> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let q = old_r.checked_div(r).ok_or(MathError::Overflow)?;
+        (old_r, r) = (r, subtract_multiple(old_r, q, r)?);
+        (old_x, x) = (x, subtract_multiple(old_x, q, x)?);
+        (old_y, y) = (y, subtract_multiple(old_y, q, y)?);
+    }
+    if old_r.is_negative() {
+        let negate = |v: T| T::ZERO.checked_sub(v).ok_or(MathError::Overflow);
+        return Ok((negate(old_r)?, negate(old_x)?, negate(old_y)?));
+    }
+    Ok((old_r, old_x, old_y))
+}
+
+/// `base` to the power of `exponent`, modulo `modulus`, in `0..modulus`.
+#[allow(dead_code)]
+pub fn mod_pow<T: Integer>(base: T, exponent: u64, modulus: T) -> Result<T, MathError> {
+    let m = positive_modulus(modulus)?;
+    let mut base = residue(base, m);
+    let mut exponent = exponent;
+    let mut result = 1 % m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exponent >>= 1;
+    }
+    Ok(T::from_u128(result).unwrap())
+}
+
+/// The `x` in `0..modulus` with `a * x ≡ 1`, which exists when `a` and `modulus` are coprime.
+#[allow(dead_code)]
+pub fn mod_inverse<T: Integer>(a: T, modulus: T) -> Result<T, MathError> {
+    let m = positive_modulus(modulus)?;
+    let inverse = inverse_u128(residue(a, m), m).ok_or(MathError::NotInvertible)?;
+    Ok(T::from_u128(inverse).unwrap())
+}
+
+/// Solves `x ≡ residue (mod modulus)` for every pair in `congruences`, returning the smallest
+/// non-negative solution and the modulus all solutions repeat with. The moduli need not be
+/// coprime, in which case the congruences may contradict each other.
+#[allow(dead_code)]
+pub fn crt<T: Integer>(congruences: &[(T, T)]) -> Result<(T, T), MathError> {
+    let (mut x, mut m) = (0u128, 1u128);
+    for &(residue_n, modulus_n) in congruences {
+        let n = positive_modulus(modulus_n)?;
+        let a = residue(residue_n, n);
+
+        let g = gcd_u128(m, n);
+        let diff = sub_mod(a, x % n, n);
+        if !diff.is_multiple_of(g) {
+            return Err(MathError::NoSolution);
+        }
+        let lcm = (m / g).checked_mul(n).ok_or(MathError::Overflow)?;
+        T::from_u128(lcm).ok_or(MathError::Overflow)?;
+
+        // x + m * k ≡ a (mod n) reduces to (m / g) * k ≡ diff / g (mod n / g)
+        let reduced = n / g;
+        let k = mul_mod(
+            diff / g,
+            inverse_u128(m / g % reduced, reduced).unwrap(),
+            reduced,
+        );
+        x += m * k;
+        m = lcm;
+    }
+    Ok((T::from_u128(x).unwrap(), T::from_u128(m).unwrap()))
+}
+
+fn positive_modulus<T: Integer>(modulus: T) -> Result<u128, MathError> {
+    if modulus.is_negative() || modulus == T::ZERO {
+        return Err(MathError::InvalidModulus);
+    }
+    Ok(modulus.unsigned_abs())
+}
+
+/// `value` reduced into `0..m`.
+fn residue<T: Integer>(value: T, m: u128) -> u128 {
+    let r = value.unsigned_abs() % m;
+    if value.is_negative() && r != 0 {
+        m - r
+    } else {
+        r
+    }
+}
+
+fn subtract_multiple<T: Integer>(a: T, q: T, b: T) -> Result<T, MathError> {
+    q.checked_mul(b)
+        .and_then(|qb| a.checked_sub(qb))
+        .ok_or(MathError::Overflow)
+}
+
+fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn lcm_u128(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd_u128(a, b)).checked_mul(b)
+}
+
+/// Moduli come from the supported types, so they are below 2^127 and sums of residues fit.
+fn add_mod(a: u128, b: u128, m: u128) -> u128 {
+    let sum = a + b;
+    if sum >= m {
+        sum - m
+    } else {
+        sum
+    }
+}
+
+fn sub_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a >= b {
+        a - b
+    } else {
+        m - (b - a)
+    }
+}
+
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+    // Double and add, which only ever needs sums of two residues
+    let (mut a, mut b, mut result) = (a % m, b, 0);
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+    result
+}
+
+/// Extended Euclid keeping only the coefficient of `a`, reduced modulo `m` so it stays unsigned.
+fn inverse_u128(a: u128, m: u128) -> Option<u128> {
+    let (mut old_r, mut r) = (m, a % m);
+    let (mut old_t, mut t) = (0, 1 % m);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_t, t) = (t, sub_mod(old_t, mul_mod(q % m, t, m), m));
+    }
+    (old_r == 1 || m == 1).then_some(old_t)
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12i64, -18), 6);
+        assert_eq!(lcm(4u64, 6), 12);
+        assert_eq!(lcm(0i128, 5), 0);
+        assert_eq!(checked_gcd(i64::MIN, 0), Err(MathError::Overflow));
+        assert_eq!(
+            checked_lcm(u64::MAX, u64::MAX - 1),
+            Err(MathError::Overflow)
+        );
+
+        let (g, x, y) = extended_gcd(240i64, 46);
+        assert_eq!((g, 240 * x + 46 * y), (2, 2));
+        assert_eq!(checked_sum([i64::MAX, 1]), Err(MathError::Overflow));
+        assert_eq!(checked_product([3u64, 4, 5]), Ok(60));
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_pow(4i64, 13, 497), Ok(445));
+        assert_eq!(mod_pow(-2i64, 3, 5), Ok(2));
+        assert_eq!(mod_pow(3u64, 0, 1), Ok(0));
+        assert_eq!(mod_inverse(3i64, 11), Ok(4));
+        assert_eq!(mod_inverse(-3i64, 11), Ok(7));
+        assert_eq!(mod_inverse(6u64, 9), Err(MathError::NotInvertible));
+        assert_eq!(mod_inverse(1i64, 0), Err(MathError::InvalidModulus));
+
+        // Big enough that the product of two residues overflows u128
+        let p = 170141183460469231731687303715884105727i128; // 2^127 - 1
+        assert_eq!(mod_pow(2, 127, p), Ok(1));
+        assert_eq!(mod_pow(p - 1, 2, p), Ok(1));
+        assert_eq!(mod_inverse(2, p), Ok(1 << 126));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2i64, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        assert_eq!(crt(&[(-1i64, 4), (3, 6)]), Ok((3, 12)));
+        assert_eq!(crt(&[(1u64, 4), (2, 6)]), Err(MathError::NoSolution));
+        assert_eq!(crt::<i64>(&[]), Ok((0, 1)));
+        assert_eq!(
+            crt(&[(0i64, i64::MAX), (1, i64::MAX - 1)]),
+            Err(MathError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// How often a cache could answer a lookup.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl Stats {
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl std::fmt::Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate)",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0
+        )
+    }
+}
+
+thread_local! {
+    static REPORTS: RefCell<Vec<(&'static str, Stats)>> = const { RefCell::new(vec![]) };
+}
+
+/// Adds `stats` to what has been reported under `name` since the last [`take_reports`].
+pub fn report(name: &'static str, stats: Stats) {
+    if stats == Stats::default() {
+        return;
+    }
+    REPORTS.with_borrow_mut(
+        |reports| match reports.iter_mut().find(|(other, _)| *other == name) {
+            Some((_, total)) => {
+                total.hits += stats.hits;
+                total.misses += stats.misses;
+            }
+            None => reports.push((name, stats)),
+        },
+    );
+}
+
+/// Everything reported on this thread so far, in the order the caches were first used.
+pub fn take_reports() -> Vec<(&'static str, Stats)> {
+    REPORTS.take()
+}
+
+/// A cache of results by argument for recursive functions, counting hits and misses. The
+/// counts are [`report`]ed under its name when it is dropped.
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+pub struct Memo<A, R> {
+    name: &'static str,
+    cache: HashMap<A, R>,
+    /// Cached arguments from oldest to newest, kept only with a capacity.
+    order: VecDeque<A>,
+    capacity: Option<usize>,
+    stats: Stats,
+}
+
+#[allow(dead_code)]
+impl<A: Clone + Eq + Hash, R: Clone> Memo<A, R> {
+    pub fn new(name: &'static str) -> Memo<A, R> {
+        Memo {
+            name,
+            cache: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: None,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Holds at most `capacity` results, evicting the oldest one to make room for a new one.
+    pub fn capacity(mut self, capacity: usize) -> Memo<A, R> {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// The cached result for `arg`, counting the lookup as a hit or a miss.
+    pub fn get(&mut self, arg: &A) -> Option<R> {
+        let value = self.cache.get(arg).cloned();
+        match value {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        value
+    }
+
+    pub fn insert(&mut self, arg: A, value: R) {
+        let Some(capacity) = self.capacity else {
+            self.cache.insert(arg, value);
+            return;
+        };
+        if capacity == 0 {
+            return;
+        }
+        if let Some(cached) = self.cache.get_mut(&arg) {
+            *cached = value;
+            return;
+        }
+        if self.cache.len() >= capacity {
+            let oldest = self.order.pop_front().unwrap();
+            self.cache.remove(&oldest);
+        }
+        self.order.push_back(arg.clone());
+        self.cache.insert(arg, value);
+    }
+
+    /// Returns the cached result for `arg`, or computes it with `f`. Recursive calls go back
+    /// through the memo, so `f` is usually a `fn` item calling `memo.call(.., f)` itself:
+    ///
+    /// ```ignore
+    /// fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+    ///     if n < 2 { n } else { memo.call(n - 1, fib) + memo.call(n - 2, fib) }
+    /// }
+    /// ```
+    pub fn call(&mut self, arg: A, f: impl FnOnce(&mut Self, A) -> R) -> R {
+        if let Some(value) = self.get(&arg) {
+            return value;
+        }
+        let value = f(self, arg.clone());
+        self.insert(arg, value.clone());
+        value
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Reports the counts so far and starts counting from zero, keeping the cached results.
+    pub fn flush_stats(&mut self) {
+        report(self.name, std::mem::take(&mut self.stats));
+    }
+}
+
+impl<A, R> Drop for Memo<A, R> {
+    fn drop(&mut self) {
+        report(self.name, std::mem::take(&mut self.stats));
+    }
+}
+
+/// Defines a function whose results are cached by its arguments in a thread local [`Memo`],
+/// which keeps them across calls. Recursive calls inside the body hit the cache too. The
+/// arguments must be `Clone + Eq + Hash` and the result `Clone`.
+///
+/// ```ignore
+/// memoize! {
+///     capacity = 10_000;
+///     fn ways(stones: u64, blinks: u32) -> u64 { .. }
+/// }
+/// ```
+///
+/// Statistics are reported each time an outermost call returns.
+#[allow(unused_macros)]
+macro_rules! memoize {
+    (
+        $(capacity = $capacity:expr;)?
+        $vis:vis fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty $body:block
+    ) => {
+        $vis fn $name($($arg: $ty),*) -> $ret {
+            thread_local! {
+                static CACHE: std::cell::RefCell<$crate::memo::Memo<($($ty,)*), $ret>> =
+                    std::cell::RefCell::new(
+                        $crate::memo::Memo::new(stringify!($name))$(.capacity($capacity))?
+                    );
+                static DEPTH: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+            }
+
+            let key = ($($arg.clone(),)*);
+            if let Some(value) = CACHE.with_borrow_mut(|cache| cache.get(&key)) {
+                if DEPTH.get() == 0 {
+                    CACHE.with_borrow_mut(|cache| cache.flush_stats());
+                }
+                return value;
+            }
+
+            // Leaves the depth right even if the body panics
+            struct Depth;
+            impl Drop for Depth {
+                fn drop(&mut self) {
+                    DEPTH.set(DEPTH.get() - 1);
+                }
+            }
+
+            // The cache must not stay borrowed while the body recurses
+            DEPTH.set(DEPTH.get() + 1);
+            let depth = Depth;
+            #[allow(clippy::redundant_closure_call)]
+            let value = (move || -> $ret { $body })();
+            drop(depth);
+
+            CACHE.with_borrow_mut(|cache| {
+                cache.insert(key, value.clone());
+                if DEPTH.get() == 0 {
+                    cache.flush_stats();
+                }
+            });
+            value
+        }
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use memoize;
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        if n < 2 {
+            n
+        } else {
+            memo.call(n - 1, fib) + memo.call(n - 2, fib)
+        }
+    }
+
+    memoize! {
+        fn paths(width: usize, height: usize) -> u64 {
+            if width == 0 || height == 0 {
+                return 1;
+            }
+            paths(width - 1, height) + paths(width, height - 1)
+        }
+    }
+
+    #[test]
+    fn test_wrapper() {
+        let mut memo = Memo::new("fib");
+        assert_eq!(memo.call(90, fib), 2880067194370816120);
+        assert_eq!(memo.len(), 91);
+        assert_eq!(
+            memo.stats(),
+            Stats {
+                hits: 88,
+                misses: 91
+            }
+        );
+
+        let mut small = Memo::new("fib").capacity(10);
+        assert_eq!(small.call(30, fib), 832040);
+        assert!(small.len() <= 10);
+        drop(small);
+        assert_eq!(take_reports()[0].0, "fib");
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut memo = Memo::new("squares").capacity(2);
+        memo.insert(1, 1);
+        memo.insert(2, 4);
+        memo.insert(1, 1);
+        memo.insert(3, 9);
+        assert_eq!(memo.len(), 2);
+        assert_eq!(memo.get(&1), None);
+        assert_eq!(memo.get(&2), Some(4));
+        assert_eq!(memo.get(&3), Some(9));
+        take_reports();
+    }
+
+    memoize! {
+        fn fragile(fail: bool) -> u64 {
+            assert!(!fail, "failing on purpose");
+            1
+        }
+    }
+
+    #[test]
+    fn test_panic_in_body() {
+        let result = std::panic::catch_unwind(|| fragile(true));
+        assert!(result.is_err());
+        assert!(take_reports().is_empty());
+        // An outermost call again, so both misses are reported as soon as it returns
+        assert_eq!(fragile(false), 1);
+        assert_eq!(take_reports()[0].1, Stats { hits: 0, misses: 2 });
+    }
+
+    #[test]
+    fn test_macro() {
+        assert_eq!(paths(16, 16), 601080390);
+        let reports = take_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].0, "paths");
+        // every corner but (0, 0), which no call recurses into
+        assert_eq!(reports[0].1.misses, 17 * 17 - 1);
+
+        assert_eq!(paths(3, 3), 20);
+        assert_eq!(take_reports()[0].1, Stats { hits: 1, misses: 0 });
+    }
+}
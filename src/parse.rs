@@ -0,0 +1,257 @@
+/// An error raised while parsing a puzzle input, pointing at the offending line and column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    line: usize,
+    column: usize,
+    source: String,
+    message: String,
+    expected: String,
+}
+
+impl ParseError {
+    /// `line` is the zero-based line index and `column` the byte offset into `source`. A
+    /// column inside a multi-byte character is moved back to the start of that character.
+    pub fn new(
+        line: usize,
+        source: &str,
+        column: usize,
+        message: impl Into<String>,
+        expected: impl Into<String>,
+    ) -> ParseError {
+        let mut column = column.min(source.len());
+        while !source.is_char_boundary(column) {
+            column -= 1;
+        }
+        ParseError {
+            line,
+            column,
+            source: source.to_string(),
+            message: message.into(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Builds an error pointing at `part`, which must be a subslice of `source`.
+    pub fn at(
+        line: usize,
+        source: &str,
+        part: &str,
+        message: impl Into<String>,
+        expected: impl Into<String>,
+    ) -> ParseError {
+        ParseError::new(line, source, offset_of(source, part), message, expected)
+    }
+
+    pub fn line(&self) -> usize {
+        self.line + 1
+    }
+
+    pub fn column(&self) -> usize {
+        self.source[..self.column].chars().count() + 1
+    }
+
+    /// Shifts the error down by `lines`, for errors raised while parsing a later section of an input.
+    pub fn offset_lines(mut self, lines: usize) -> ParseError {
+        self.line += lines;
+        self
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let number = self.line().to_string();
+        let gutter = " ".repeat(number.len());
+
+        // Keep tabs in the padding so the caret lines up with the quoted source
+        let mut padding = String::new();
+        for c in self.source[..self.column].chars() {
+            padding.push(if c == '\t' { '\t' } else { ' ' });
+        }
+
+        writeln!(f, "error: {}", self.message)?;
+        writeln!(
+            f,
+            "{}--> line {}, column {}",
+            gutter,
+            self.line(),
+            self.column()
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", number, self.source)?;
+        writeln!(f, "{} | {}^ expected {}", gutter, padding, self.expected)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Returns the byte offset of `part` within `source`. `part` must be a subslice of `source`,
+/// not just equal to some part of it.
+pub fn offset_of(source: &str, part: &str) -> usize {
+    let start = source.as_ptr() as usize;
+    let part = part.as_ptr() as usize;
+    debug_assert!(
+        (start..=start + source.len()).contains(&part),
+        "offset_of called with a part outside of its source"
+    );
+    part.saturating_sub(start).min(source.len())
+}
+
+/// Parses `token`, a subslice of `source`, reporting a caret under it on failure.
+pub fn number<T: std::str::FromStr>(
+    line: usize,
+    source: &str,
+    token: &str,
+) -> Result<T, ParseError> {
+    token.parse::<T>().map_err(|_| {
+        let name = std::any::type_name::<T>();
+        ParseError::at(
+            line,
+            source,
+            token,
+            format!("invalid {} {:?}", name, token),
+            format!("a value of type {}", name),
+        )
+    })
+}
+
+/// Parses every line of `input` with `parse`, which gets the zero-based line index and the
+/// line itself, stopping at the first error.
+pub fn lines<T>(
+    input: &str,
+    mut parse: impl FnMut(usize, &str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| parse(idx, line))
+        .collect()
+}
+
+/// Splits `input` at runs of blank lines, yielding each section along with the index of its
+/// first line so errors inside it can be shifted with [`ParseError::offset_lines`].
+pub fn sections(input: &str) -> Vec<(usize, &str)> {
+    let mut sections = vec![];
+    let mut start = None;
+    let mut end = 0;
+    let mut offset = 0;
+    for (idx, line) in input.split('\n').enumerate() {
+        let line_end = offset + line.len();
+        if line.trim().is_empty() {
+            if let Some((first, from)) = start.take() {
+                sections.push((first, &input[from..end]));
+            }
+        } else {
+            start.get_or_insert((idx, offset));
+            end = line_end;
+        }
+        offset = line_end + 1;
+    }
+    if let Some((first, from)) = start {
+        sections.push((first, &input[from..end]));
+    }
+    sections
+}
+
+/// Extracts every integer in `source`, taking a `-` right before the digits as its sign.
+#[allow(dead_code)]
+pub fn ints<T: std::str::FromStr>(line: usize, source: &str) -> Result<Vec<T>, ParseError> {
+    let bytes = source.as_bytes();
+    let mut numbers = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let negative = bytes[i] == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        if !negative && !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        numbers.push(number(line, source, &source[start..i])?);
+    }
+    Ok(numbers)
+}
+
+/// Parses the `delimiter` separated tokens of `source`.
+pub fn list<T: std::str::FromStr>(
+    line: usize,
+    source: &str,
+    delimiter: &str,
+) -> Result<Vec<T>, ParseError> {
+    source
+        .split(delimiter)
+        .map(|token| number(line, source, token))
+        .collect()
+}
+
+/// Parses `source` as a key and a value around the first `separator`.
+pub fn pair<K: std::str::FromStr, V: std::str::FromStr>(
+    line: usize,
+    source: &str,
+    separator: &str,
+) -> Result<(K, V), ParseError> {
+    let Some((key, val)) = source.split_once(separator) else {
+        // Point just past the key, where the separator should have started
+        let column = source
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(source.len());
+        return Err(ParseError::new(
+            line,
+            source,
+            column,
+            "missing separator",
+            format!("{:?}", separator),
+        ));
+    };
+    Ok((number(line, source, key)?, number(line, source, val)?))
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_caret_position() {
+        let source = "12\t34";
+        let error = ParseError::new(2, source, 2, "missing separator", "three spaces");
+        assert_eq!((error.line(), error.column()), (3, 3));
+        assert_eq!(
+            error.to_string(),
+            "error: missing separator\n --> line 3, column 3\n  |\n3 | 12\t34\n  |   ^ expected three spaces\n"
+        );
+    }
+
+    #[test]
+    fn test_number() {
+        let source = "7 4 x 2";
+        let tokens = source.split(' ').collect::<Vec<_>>();
+        assert_eq!(number::<i64>(0, source, tokens[0]), Ok(7));
+        assert_eq!(number::<i64>(0, source, tokens[2]).unwrap_err().column(), 5);
+
+        // A byte offset inside 'é' points at the character itself
+        let error = ParseError::new(0, "né!", 2, "bad", "good");
+        assert_eq!(error.column(), 2);
+        assert!(error.to_string().contains("né!"));
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(
+            ints::<i64>(0, "p=0,-4 v=-3--5 x-y"),
+            Ok(vec![0, -4, -3, -5])
+        );
+        assert_eq!(list::<u8>(0, "1,2,3", ","), Ok(vec![1, 2, 3]));
+        assert_eq!(list::<u8>(0, "1,2,300", ",").unwrap_err().column(), 5);
+        assert_eq!(
+            pair::<String, i64>(0, "a: 7", ": "),
+            Ok(("a".to_string(), 7))
+        );
+        assert_eq!(pair::<i64, i64>(0, "12\t7", "   ").unwrap_err().column(), 3);
+
+        let input = "a\nb\n\n\nc\n\nd\n";
+        assert_eq!(sections(input), vec![(0, "a\nb"), (4, "c"), (6, "d")]);
+    }
+}
@@ -0,0 +1,127 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A position or offset on the plane, with `y` growing downwards like the rows of an input.
+///
+/// Points order by row first, so sorting them gives reading order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub y: i64,
+    pub x: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+
+    /// Converts a row-major index into a grid that is `width` cells wide.
+    pub fn from_index(index: usize, width: usize) -> Point {
+        Point::new((index % width) as i64, (index / width) as i64)
+    }
+
+    /// Converts into a row-major index, if the point lies within a `width` by `height` grid.
+    pub fn to_index(self, width: usize, height: usize) -> Option<usize> {
+        let in_bounds =
+            self.x >= 0 && self.x < width as i64 && self.y >= 0 && self.y < height as i64;
+        in_bounds.then(|| self.y as usize * width + self.x as usize)
+    }
+
+    pub fn manhattan(self, other: Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    pub fn chebyshev(self, other: Point) -> i64 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((x, y): (i64, i64)) -> Self {
+        Point::new(x, y)
+    }
+}
+
+impl From<Point> for (i64, i64) {
+    fn from(point: Point) -> Self {
+        (point.x, point.y)
+    }
+}
+
+impl std::fmt::Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, factor: i64) -> Point {
+        Point::new(self.x * factor, self.y * factor)
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Point::new(3, -2);
+        let b = Point::new(-1, 4);
+        assert_eq!(a + b, Point::new(2, 2));
+        assert_eq!(a - b, Point::new(4, -6));
+        assert_eq!(-a, Point::new(-3, 2));
+        assert_eq!(a * 3, Point::new(9, -6));
+        assert_eq!(a.manhattan(b), 10);
+        assert_eq!(a.chebyshev(b), 6);
+        assert!(Point::new(5, 0) < Point::new(0, 1));
+    }
+
+    #[test]
+    fn test_indices() {
+        assert_eq!(Point::from_index(7, 3), Point::new(1, 2));
+        assert_eq!(Point::new(1, 2).to_index(3, 3), Some(7));
+        assert_eq!(Point::new(3, 0).to_index(3, 3), None);
+        assert_eq!(Point::new(0, -1).to_index(3, 3), None);
+    }
+}
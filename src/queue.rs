@@ -0,0 +1,343 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::grid::Grid;
+use crate::point::Point;
+use crate::rng::Rng;
+use crate::scale;
+use crate::search::{self, Neighborhood};
+
+/// A min-priority queue of dense item indices, as used by [`search::astar_with`].
+pub trait MinQueue {
+    /// Queues `item`, or lowers its priority if it is queued already. Queues without
+    /// decrease-key may instead keep the old entry, and hand it out later with its outdated
+    /// priority.
+    fn push(&mut self, item: usize, priority: u64);
+
+    /// Removes an item with the lowest priority.
+    fn pop(&mut self) -> Option<(usize, u64)>;
+}
+
+impl MinQueue for BinaryHeap<Reverse<(u64, usize)>> {
+    fn push(&mut self, item: usize, priority: u64) {
+        BinaryHeap::push(self, Reverse((priority, item)));
+    }
+
+    fn pop(&mut self) -> Option<(usize, u64)> {
+        BinaryHeap::pop(self).map(|Reverse((priority, item))| (item, priority))
+    }
+}
+
+impl<Q: MinQueue + ?Sized> MinQueue for Box<Q> {
+    fn push(&mut self, item: usize, priority: u64) {
+        (**self).push(item, priority);
+    }
+
+    fn pop(&mut self) -> Option<(usize, u64)> {
+        (**self).pop()
+    }
+}
+
+const ABSENT: usize = usize::MAX;
+
+/// A binary min-heap that knows where each item sits, so it holds every item at most once
+/// and can lower an item's priority in place.
+#[derive(Clone, Debug, Default)]
+pub struct IndexedHeap<P> {
+    heap: Vec<(P, usize)>,
+    positions: Vec<usize>,
+}
+
+impl<P: Ord + Copy> IndexedHeap<P> {
+    pub fn new() -> IndexedHeap<P> {
+        IndexedHeap {
+            heap: vec![],
+            positions: vec![],
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, item: usize) -> bool {
+        self.position(item).is_some()
+    }
+
+    /// The priority `item` is queued with.
+    #[allow(dead_code)]
+    pub fn priority(&self, item: usize) -> Option<P> {
+        self.position(item).map(|position| self.heap[position].0)
+    }
+
+    /// Queues `item` or lowers its priority, returning whether anything changed. A higher
+    /// priority than the queued one is ignored.
+    pub fn push_or_decrease(&mut self, item: usize, priority: P) -> bool {
+        match self.position(item) {
+            Some(position) if priority < self.heap[position].0 => {
+                self.heap[position].0 = priority;
+                self.sift_up(position);
+                true
+            }
+            Some(_) => false,
+            None => {
+                if item >= self.positions.len() {
+                    self.positions.resize(item + 1, ABSENT);
+                }
+                self.heap.push((priority, item));
+                self.positions[item] = self.heap.len() - 1;
+                self.sift_up(self.heap.len() - 1);
+                true
+            }
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn peek(&self) -> Option<(usize, P)> {
+        self.heap.first().map(|&(priority, item)| (item, priority))
+    }
+
+    pub fn pop(&mut self) -> Option<(usize, P)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        self.swap(0, self.heap.len() - 1);
+        let (priority, item) = self.heap.pop().unwrap();
+        self.positions[item] = ABSENT;
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        Some((item, priority))
+    }
+
+    fn position(&self, item: usize) -> Option<usize> {
+        self.positions
+            .get(item)
+            .copied()
+            .filter(|&position| position != ABSENT)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a].1] = a;
+        self.positions[self.heap[b].1] = b;
+    }
+
+    fn sift_up(&mut self, mut position: usize) {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if self.heap[parent].0 <= self.heap[position].0 {
+                break;
+            }
+            self.swap(parent, position);
+            position = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut position: usize) {
+        loop {
+            let left = 2 * position + 1;
+            let right = left + 1;
+            let mut smallest = position;
+            if left < self.heap.len() && self.heap[left].0 < self.heap[smallest].0 {
+                smallest = left;
+            }
+            if right < self.heap.len() && self.heap[right].0 < self.heap[smallest].0 {
+                smallest = right;
+            }
+            if smallest == position {
+                break;
+            }
+            self.swap(smallest, position);
+            position = smallest;
+        }
+    }
+}
+
+impl MinQueue for IndexedHeap<u64> {
+    fn push(&mut self, item: usize, priority: u64) {
+        self.push_or_decrease(item, priority);
+    }
+
+    fn pop(&mut self) -> Option<(usize, u64)> {
+        IndexedHeap::pop(self)
+    }
+}
+
+/// Dial's bucket queue: one bucket per priority from the lowest queued one upwards. Pushing
+/// and popping take constant time when priorities stay close together and never drop below
+/// the last one popped, as in a search over small integer weights.
+#[derive(Clone, Debug, Default)]
+pub struct BucketQueue {
+    buckets: VecDeque<Vec<usize>>,
+    base: u64,
+    popped: u64,
+    len: usize,
+}
+
+impl BucketQueue {
+    pub fn new() -> BucketQueue {
+        BucketQueue {
+            buckets: VecDeque::new(),
+            base: 0,
+            popped: 0,
+            len: 0,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl MinQueue for BucketQueue {
+    /// Panics if `priority` is below one that was popped already.
+    fn push(&mut self, item: usize, priority: u64) {
+        assert!(
+            priority >= self.popped,
+            "priority {} is below the last popped {}",
+            priority,
+            self.popped
+        );
+        if self.len == 0 {
+            self.buckets.clear();
+            self.base = priority;
+        }
+        while priority < self.base {
+            self.buckets.push_front(vec![]);
+            self.base -= 1;
+        }
+        let offset = (priority - self.base) as usize;
+        if offset >= self.buckets.len() {
+            self.buckets.resize_with(offset + 1, Vec::new);
+        }
+        self.buckets[offset].push(item);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<(usize, u64)> {
+        while let Some(bucket) = self.buckets.front_mut() {
+            if let Some(item) = bucket.pop() {
+                self.len -= 1;
+                self.popped = self.base;
+                return Some((item, self.base));
+            }
+            self.buckets.pop_front();
+            self.base += 1;
+        }
+        None
+    }
+}
+
+/// Times Dijkstra across random `size` by `size` grids with weights 1 to 9 using each queue.
+pub fn bench(size: usize) {
+    println!(
+        "Shortest path across a {0}x{0} grid of weights 1 to 9",
+        size
+    );
+    if cfg!(debug_assertions) {
+        println!("Warning: debug build, use --release for representative timings");
+    }
+
+    let mut rng = Rng::new(size as u64);
+    let cells = (0..size * size).map(|_| rng.range(1..10) as u64).collect();
+    let grid = Grid::new(cells, size, size);
+    let goal = Point::new(size as i64 - 1, size as i64 - 1);
+    let neighbors = |&point: &Point| {
+        Neighborhood::Four
+            .around(point)
+            .filter_map(|next| Some((next, *grid.get(next)?)))
+            .collect::<Vec<_>>()
+    };
+    let shortest = |queue: Box<dyn MinQueue>| {
+        search::dijkstra_with(queue, Point::ORIGIN, neighbors, |&point| point == goal)
+            .map(|(cost, _)| cost)
+    };
+
+    type MakeQueue = fn() -> Box<dyn MinQueue>;
+    let queues: [(&str, MakeQueue); 3] = [
+        ("BinaryHeap", || Box::new(BinaryHeap::new())),
+        ("IndexedHeap", || Box::new(IndexedHeap::new())),
+        ("BucketQueue", || Box::new(BucketQueue::new())),
+    ];
+    let expected = shortest(queues[0].1());
+    println!("{:>14} {:>12} {:>8}", "queue", "time", "cost");
+    for (name, queue) in queues {
+        let time = scale::measure(|| shortest(queue()));
+        let cost = shortest(queue());
+        assert_eq!(cost, expected, "{} found a different path", name);
+        println!("{:>14} {:>12.3?} {:>8}", name, time, cost.unwrap());
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    fn drain(queue: &mut impl MinQueue) -> Vec<(usize, u64)> {
+        std::iter::from_fn(|| queue.pop()).collect()
+    }
+
+    #[test]
+    fn test_indexed_heap() {
+        let mut heap = IndexedHeap::new();
+        assert!(heap.push_or_decrease(3, 30));
+        assert!(heap.push_or_decrease(1, 10));
+        assert!(heap.push_or_decrease(7, 20));
+        assert!(heap.push_or_decrease(3, 5));
+        assert!(!heap.push_or_decrease(1, 15));
+        assert_eq!((heap.len(), heap.priority(3)), (3, Some(5)));
+        assert_eq!(heap.peek(), Some((3, 5)));
+        assert_eq!(drain(&mut heap), [(3, 5), (1, 10), (7, 20)]);
+        assert!(!heap.contains(3));
+    }
+
+    #[test]
+    fn test_bucket_queue() {
+        let mut queue = BucketQueue::new();
+        queue.push(4, 12);
+        queue.push(2, 10);
+        queue.push(9, 10);
+        assert_eq!(queue.pop(), Some((9, 10)));
+        queue.push(5, 11);
+        assert_eq!(queue.len(), 3);
+        assert_eq!(drain(&mut queue), [(2, 10), (5, 11), (4, 12)]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_searches_agree() {
+        let grid = Grid::parse("1163751\n1381373\n2136511\n3694931\n", |c| {
+            c.to_digit(10).map(u64::from).ok_or("a digit")
+        })
+        .unwrap();
+        let goal = Point::new(6, 3);
+        let neighbors = |&point: &Point| {
+            Neighborhood::Four
+                .around(point)
+                .filter_map(|next| Some((next, *grid.get(next)?)))
+                .collect::<Vec<_>>()
+        };
+        let is_goal = |&point: &Point| point == goal;
+
+        let expected = search::dijkstra(Point::ORIGIN, neighbors, is_goal).unwrap();
+        let heap = search::dijkstra_with(IndexedHeap::new(), Point::ORIGIN, neighbors, is_goal);
+        let buckets = search::dijkstra_with(BucketQueue::new(), Point::ORIGIN, neighbors, is_goal);
+        assert_eq!(heap.unwrap().0, expected.0);
+        assert_eq!(buckets.unwrap().0, expected.0);
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::ops::{Bound, Range, RangeBounds};
+
+/// A set of integers stored as disjoint, non-adjacent half-open intervals.
+///
+/// Every method takes any kind of range, so `1..=3`, `1..4` and `..4` all work. Unbounded
+/// ends stop at `i64::MIN` and `i64::MAX`, which means `i64::MAX` itself cannot be a member.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RangeSet {
+    // start -> end, with every end strictly before the next start
+    ranges: BTreeMap<i64, i64>,
+}
+
+impl RangeSet {
+    pub fn new() -> RangeSet {
+        RangeSet {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    /// Adds every value in `range`, merging it with any interval it overlaps or touches.
+    pub fn insert(&mut self, range: impl RangeBounds<i64>) {
+        let Range { mut start, mut end } = half_open(range);
+        if start >= end {
+            return;
+        }
+        if let Some((&before, &before_end)) = self.ranges.range(..start).next_back() {
+            if before_end >= start {
+                start = before;
+                end = end.max(before_end);
+            }
+        }
+        let merged = self
+            .ranges
+            .range(start..=end)
+            .map(|(&s, &e)| (s, e))
+            .collect::<Vec<_>>();
+        for (s, e) in merged {
+            self.ranges.remove(&s);
+            end = end.max(e);
+        }
+        self.ranges.insert(start, end);
+    }
+
+    /// Removes every value in `range`, splitting an interval if it covers both ends.
+    pub fn remove(&mut self, range: impl RangeBounds<i64>) {
+        let Range { start, end } = half_open(range);
+        if start >= end {
+            return;
+        }
+        let overlapping = self.overlapping(start..end).collect::<Vec<_>>();
+        for Range { start: s, end: e } in overlapping {
+            self.ranges.remove(&s);
+            if s < start {
+                self.ranges.insert(s, start);
+            }
+            if e > end {
+                self.ranges.insert(end, e);
+            }
+        }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.ranges
+            .range(..=value)
+            .next_back()
+            .is_some_and(|(_, &end)| end > value)
+    }
+
+    /// Whether every value in `range` is a member.
+    pub fn contains_range(&self, range: impl RangeBounds<i64>) -> bool {
+        let Range { start, end } = half_open(range);
+        start >= end
+            || self
+                .ranges
+                .range(..=start)
+                .next_back()
+                .is_some_and(|(_, &e)| e >= end)
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        let mut union = self.clone();
+        for range in other.ranges() {
+            union.insert(range);
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut intersection = RangeSet::new();
+        for range in other.ranges() {
+            for overlap in self.overlapping(range.clone()) {
+                intersection.insert(overlap.start.max(range.start)..overlap.end.min(range.end));
+            }
+        }
+        intersection
+    }
+
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut difference = self.clone();
+        for range in other.ranges() {
+            difference.remove(range);
+        }
+        difference
+    }
+
+    /// The number of integers in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|(&s, &e)| e.abs_diff(s)).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The disjoint intervals making up the set, in increasing order.
+    pub fn ranges(&self) -> impl Iterator<Item = Range<i64>> + '_ {
+        self.ranges.iter().map(|(&start, &end)| start..end)
+    }
+
+    /// The stored intervals that share at least one value with `range`.
+    fn overlapping(&self, range: Range<i64>) -> impl Iterator<Item = Range<i64>> + '_ {
+        let before = self
+            .ranges
+            .range(..range.start)
+            .next_back()
+            .filter(|(_, &end)| end > range.start);
+        before
+            .into_iter()
+            .chain(self.ranges.range(range.start..range.end))
+            .map(|(&start, &end)| start..end)
+    }
+}
+
+impl<R: RangeBounds<i64>> FromIterator<R> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = R>>(ranges: I) -> Self {
+        let mut set = RangeSet::new();
+        for range in ranges {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+impl std::fmt::Display for RangeSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ranges = self
+            .ranges()
+            .map(|range| format!("{}..{}", range.start, range.end))
+            .collect::<Vec<_>>();
+        write!(f, "{{{}}}", ranges.join(", "))
+    }
+}
+
+fn half_open(range: impl RangeBounds<i64>) -> Range<i64> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => i64::MIN,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.saturating_add(1),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => i64::MAX,
+    };
+    start..end
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+    use itertools::Itertools;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_merging() {
+        let mut set = RangeSet::from_iter([1..3, 5..7]);
+        set.insert(3..=4);
+        assert_eq!(set, RangeSet::from_iter([1..=6]));
+        assert_eq!(set.len(), 6);
+
+        set.remove(3..4);
+        assert_eq!(set.to_string(), "{1..3, 4..7}");
+        assert!(set.contains(4) && !set.contains(3) && !set.contains(7));
+        assert!(set.contains_range(4..=6) && !set.contains_range(2..5));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = RangeSet::from_iter([0..10, 20..30]);
+        let b = RangeSet::from_iter([5..=24]);
+        assert_eq!(a.union(&b), RangeSet::from_iter([0..=29]));
+        assert_eq!(a.intersection(&b), RangeSet::from_iter([5..10, 20..25]));
+        assert_eq!(a.difference(&b), RangeSet::from_iter([0..5, 25..30]));
+        assert!(a.difference(&a).is_empty());
+    }
+
+    fn ranges() -> impl Strategy<Value = Vec<(bool, i64, i64)>> {
+        prop::collection::vec((any::<bool>(), -20..20i64, -20..20i64), 0..20)
+    }
+
+    proptest! {
+        #[test]
+        fn test_matches_set(ops in ranges(), others in ranges()) {
+            let mut set = RangeSet::new();
+            let mut model = BTreeSet::new();
+            for (insert, a, b) in ops {
+                if insert {
+                    set.insert(a..=b);
+                    model.extend(a..=b);
+                } else {
+                    set.remove(a..=b);
+                    (a..=b).for_each(|v| { model.remove(&v); });
+                }
+            }
+            let other = RangeSet::from_iter(others.iter().map(|&(_, a, b)| a..b));
+            let other_model = others.iter().flat_map(|&(_, a, b)| a..b).collect::<BTreeSet<_>>();
+
+            prop_assert_eq!(set.len(), model.len() as u64);
+            prop_assert!((-25..25).all(|v| set.contains(v) == model.contains(&v)));
+            prop_assert!(set.ranges().tuple_windows().all(|(a, b)| a.end < b.start));
+
+            let members = |set: &RangeSet| set.ranges().flatten().collect::<BTreeSet<_>>();
+            prop_assert_eq!(members(&set.union(&other)), &model | &other_model);
+            prop_assert_eq!(members(&set.intersection(&other)), &model & &other_model);
+            prop_assert_eq!(members(&set.difference(&other)), &model - &other_model);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::ops::Range;
+
+/// Small seeded SplitMix64 generator, good enough for generating puzzle-like inputs.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn range(&mut self, range: Range<usize>) -> usize {
+        assert!(!range.is_empty());
+        range.start + (self.next_u64() % (range.end - range.start) as u64) as usize
+    }
+
+    /// Returns true with a probability of `1 / n`.
+    pub fn one_in(&mut self, n: usize) -> bool {
+        self.range(0..n) == 0
+    }
+
+    pub fn pick<'a, T>(&mut self, values: &'a [T]) -> &'a T {
+        &values[self.range(0..values.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for idx in (1..values.len()).rev() {
+            values.swap(idx, self.range(0..idx + 1));
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::time::{Duration, Instant};
+
+use crate::memo;
+use crate::rng::Rng;
+use crate::AdventOfCodeDay;
+
+const REPETITIONS: usize = 5;
+
+/// Runs a day on generated inputs of doubling size up to `max_size` and estimates
+/// the exponent `k` in `time ~ bytes^k` for parsing and both parts.
+pub fn run<D: AdventOfCodeDay>(max_size: usize) {
+    println!("Scaling Day {:02}", D::DAY);
+    if cfg!(debug_assertions) {
+        println!("Warning: debug build, use --release for representative timings");
+    }
+
+    let mut rng = Rng::new(D::DAY as u64);
+    let mut samples = vec![];
+    println!(
+        "{:>8} {:>10} {:>12} {:>12} {:>12}",
+        "size", "bytes", "parse", "part 1", "part 2"
+    );
+
+    // Nothing reported before the first size belongs to it
+    memo::take_reports();
+    let mut size = 8;
+    while size <= max_size {
+        let Some(input) = D::generate(size, &mut rng) else {
+            println!("Day {:02} has no input generator", D::DAY);
+            return;
+        };
+        let bytes = input.len();
+
+        let parse = measure(|| D::parse_bytes(input.as_bytes()).unwrap());
+        let parsed = D::parse_bytes(input.as_bytes()).unwrap();
+        let part_1 = measure_with(&parsed, D::part_1);
+        let part_2 = measure_with(&parsed, D::part_2);
+
+        println!(
+            "{:>8} {:>10} {:>12.3?} {:>12.3?} {:>12.3?}",
+            size, bytes, parse, part_1, part_2
+        );
+        // Summed over every repetition of both parts at this size
+        crate::print_memo_stats();
+        samples.push((bytes, [parse, part_1, part_2]));
+        size *= 2;
+    }
+
+    if samples.len() < 2 {
+        println!("Not enough sizes for a fit, increase the maximum size");
+        return;
+    }
+
+    let exponents = ["parse", "part 1", "part 2"]
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            let points = samples
+                .iter()
+                .map(|(bytes, times)| (*bytes as f64, times[idx].as_secs_f64()))
+                .collect::<Vec<_>>();
+            format!("{} k={:.2}", name, fit_exponent(&points))
+        })
+        .collect::<Vec<_>>();
+    println!("Estimated exponents: {}", exponents.join(", "));
+}
+
+pub fn measure<T>(mut f: impl FnMut() -> T) -> Duration {
+    (0..REPETITIONS)
+        .map(|_| {
+            let start = Instant::now();
+            std::hint::black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn measure_with<P: Clone>(parsed: &P, part: fn(P) -> i64) -> Duration {
+    (0..REPETITIONS)
+        .map(|_| {
+            let parsed = parsed.clone();
+            let start = Instant::now();
+            std::hint::black_box(part(parsed));
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Least-squares slope of `ln(time)` over `ln(bytes)`.
+fn fit_exponent(points: &[(f64, f64)]) -> f64 {
+    let logs = points
+        .iter()
+        .map(|(x, y)| (x.ln(), y.max(1e-9).ln()))
+        .collect::<Vec<_>>();
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance = logs
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>();
+    let variance = logs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+    covariance / variance
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fit_exponent() {
+        let quadratic = (1..6)
+            .map(|i| (i as f64 * 100.0, (i as f64 * 100.0).powi(2) * 3.0))
+            .collect::<Vec<_>>();
+        assert!((fit_exponent(&quadratic) - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::direction::{Dir8, Direction};
+use crate::grid::Grid;
+use crate::point::Point;
+use crate::queue::MinQueue;
+
+/// Finds a path with the fewest steps from `start` to a state accepted by `is_goal`.
+#[allow(dead_code)]
+pub fn bfs<S, I>(
+    start: S,
+    mut neighbors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Vec<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut visited = Visited::new(start);
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(index) = queue.pop_front() {
+        let state = visited.states[index].clone();
+        if is_goal(&state) {
+            return Some(visited.path_to(index));
+        }
+        for next in neighbors(&state) {
+            if let Ok(next) = visited.insert(next, index) {
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Finds any path from `start` to a state accepted by `is_goal`, exploring depth first.
+#[allow(dead_code)]
+pub fn dfs<S, I>(
+    start: S,
+    mut neighbors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Vec<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut visited = Visited::new(start);
+    let mut stack = vec![0];
+
+    while let Some(index) = stack.pop() {
+        let state = visited.states[index].clone();
+        if is_goal(&state) {
+            return Some(visited.path_to(index));
+        }
+        for next in neighbors(&state) {
+            if let Ok(next) = visited.insert(next, index) {
+                stack.push(next);
+            }
+        }
+    }
+    None
+}
+
+/// Finds the cheapest path from `start` to a state accepted by `is_goal`, where `neighbors`
+/// yields each reachable state along with the cost of moving there.
+#[allow(dead_code)]
+pub fn dijkstra<S, I>(
+    start: S,
+    neighbors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<(u64, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    astar(start, neighbors, |_| 0, is_goal)
+}
+
+/// Like [`dijkstra`], but keeps the frontier in `queue`, which must start out empty.
+pub fn dijkstra_with<S, I>(
+    queue: impl MinQueue,
+    start: S,
+    neighbors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<(u64, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    astar_with(queue, start, neighbors, |_| 0, is_goal)
+}
+
+/// Like [`dijkstra`], but explores states in order of cost plus `heuristic`, which must never
+/// overestimate the remaining cost to reach a goal.
+#[allow(dead_code)]
+pub fn astar<S, I>(
+    start: S,
+    neighbors: impl FnMut(&S) -> I,
+    heuristic: impl FnMut(&S) -> u64,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<(u64, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    astar_with(BinaryHeap::new(), start, neighbors, heuristic, is_goal)
+}
+
+/// Like [`astar`], but keeps the frontier in `queue`, which must start out empty. A
+/// [`BucketQueue`](crate::queue::BucketQueue) needs a heuristic that never drops by more than
+/// the cost of a step.
+pub fn astar_with<S, I>(
+    mut queue: impl MinQueue,
+    start: S,
+    mut neighbors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> u64,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<(u64, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    let mut visited = Visited::new(start);
+    let mut costs = vec![0];
+    let mut estimates = vec![heuristic(&visited.states[0])];
+    queue.push(0, estimates[0]);
+
+    while let Some((index, priority)) = queue.pop() {
+        let cost = costs[index];
+        // Queues without decrease-key hand out entries that were improved upon since
+        if priority > cost + estimates[index] {
+            continue;
+        }
+        let state = visited.states[index].clone();
+        if is_goal(&state) {
+            return Some((cost, visited.path_to(index)));
+        }
+        for (next, step) in neighbors(&state) {
+            let next_cost = cost + step;
+            let next = match visited.insert(next, index) {
+                Ok(next) => {
+                    costs.push(next_cost);
+                    estimates.push(heuristic(&visited.states[next]));
+                    next
+                }
+                Err(next) => {
+                    if next_cost >= costs[next] {
+                        continue;
+                    }
+                    costs[next] = next_cost;
+                    visited.parents[next] = Some(index);
+                    next
+                }
+            };
+            queue.push(next, next_cost + estimates[next]);
+        }
+    }
+    None
+}
+
+/// Which cells around a point count as its neighbors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    Four,
+    #[allow(dead_code)]
+    Eight,
+}
+
+impl Neighborhood {
+    pub fn around(self, point: Point) -> impl Iterator<Item = Point> {
+        let dirs: &'static [Dir8] = match self {
+            Neighborhood::Four => &[Dir8::Up, Dir8::Right, Dir8::Down, Dir8::Left],
+            Neighborhood::Eight => Dir8::ALL,
+        };
+        dirs.iter().map(move |dir| dir.step(point))
+    }
+
+    /// A lower bound on the steps between two points, usable as an A* heuristic.
+    #[allow(dead_code)]
+    pub fn distance(self, a: Point, b: Point) -> u64 {
+        match self {
+            Neighborhood::Four => a.manhattan(b) as u64,
+            Neighborhood::Eight => a.chebyshev(b) as u64,
+        }
+    }
+}
+
+/// Finds a shortest path between two cells, moving only onto cells that are `passable`.
+#[allow(dead_code)]
+pub fn grid_bfs<T>(
+    grid: &Grid<T>,
+    start: Point,
+    goal: Point,
+    neighborhood: Neighborhood,
+    passable: impl Fn(&T) -> bool,
+) -> Option<Vec<Point>> {
+    bfs(
+        start,
+        |&point| passable_around(grid, point, neighborhood, &passable),
+        |&point| point == goal,
+    )
+}
+
+/// Finds the cheapest path between two cells, where `cost` gives the price of entering a
+/// cell or `None` if it cannot be entered. Every step has to cost at least one.
+#[allow(dead_code)]
+pub fn grid_astar<T>(
+    grid: &Grid<T>,
+    start: Point,
+    goal: Point,
+    neighborhood: Neighborhood,
+    cost: impl Fn(&T) -> Option<u64>,
+) -> Option<(u64, Vec<Point>)> {
+    astar(
+        start,
+        |&point| {
+            neighborhood
+                .around(point)
+                .filter_map(|next| Some((next, cost(grid.get(next)?)?)))
+                .collect::<Vec<_>>()
+        },
+        |&point| neighborhood.distance(point, goal),
+        |&point| point == goal,
+    )
+}
+
+fn passable_around<T>(
+    grid: &Grid<T>,
+    point: Point,
+    neighborhood: Neighborhood,
+    passable: impl Fn(&T) -> bool,
+) -> Vec<Point> {
+    neighborhood
+        .around(point)
+        .filter(|&next| grid.get(next).is_some_and(&passable))
+        .collect()
+}
+
+/// Interns every discovered state and remembers where it was reached from.
+struct Visited<S> {
+    states: Vec<S>,
+    index: HashMap<S, usize>,
+    parents: Vec<Option<usize>>,
+}
+
+impl<S: Clone + Eq + Hash> Visited<S> {
+    fn new(start: S) -> Visited<S> {
+        Visited {
+            index: HashMap::from([(start.clone(), 0)]),
+            states: vec![start],
+            parents: vec![None],
+        }
+    }
+
+    /// Records `state` as reached from `parent`, returning its index if it is new and the
+    /// index it was first seen at otherwise.
+    fn insert(&mut self, state: S, parent: usize) -> Result<usize, usize> {
+        if let Some(&index) = self.index.get(&state) {
+            return Err(index);
+        }
+        let index = self.states.len();
+        self.index.insert(state.clone(), index);
+        self.states.push(state);
+        self.parents.push(Some(parent));
+        Ok(index)
+    }
+
+    fn path_to(&self, mut index: usize) -> Vec<S> {
+        let mut path = vec![self.states[index].clone()];
+        while let Some(parent) = self.parents[index] {
+            path.push(self.states[parent].clone());
+            index = parent;
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    fn maze() -> Grid<char> {
+        Grid::parse("S.#.\n#.#.\n...G\n.##.\n", Ok::<char, String>).unwrap()
+    }
+
+    #[test]
+    fn test_graph_searches() {
+        // 0 -> 1 -> 3 costs 2 + 5, 0 -> 2 -> 3 costs 4 + 1
+        let edges = |&n: &u32| match n {
+            0 => vec![(1, 2), (2, 4)],
+            1 => vec![(3, 5)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        };
+        let unweighted = |n: &u32| edges(n).into_iter().map(|(m, _)| m);
+
+        assert_eq!(bfs(0, unweighted, |&n| n == 3).unwrap().len(), 3);
+        assert_eq!(dfs(0, unweighted, |&n| n == 3).unwrap().last(), Some(&3));
+        assert_eq!(dijkstra(0, edges, |&n| n == 3), Some((5, vec![0, 2, 3])));
+        assert_eq!(bfs(0, unweighted, |&n| n == 4), None);
+    }
+
+    #[test]
+    fn test_grid_searches() {
+        let grid = maze();
+        let (start, goal) = (Point::new(0, 0), Point::new(3, 2));
+
+        let path = grid_bfs(&grid, start, goal, Neighborhood::Four, |&c| c != '#').unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!((path[0], path[5]), (start, goal));
+
+        let diagonal = grid_bfs(&grid, start, goal, Neighborhood::Eight, |&c| c != '#');
+        assert_eq!(diagonal.unwrap().len(), 4);
+
+        let cost = |&c: &char| (c != '#').then_some(1);
+        let (steps, path) = grid_astar(&grid, start, goal, Neighborhood::Four, cost).unwrap();
+        assert_eq!((steps, path.len()), (5, 6));
+    }
+}
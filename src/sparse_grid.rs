@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::ops::Index;
+
+use crate::grid::Grid;
+use crate::point::Point;
+
+/// A grid without fixed bounds that only stores the cells that were set, so coordinates
+/// may be negative and the plane may grow in any direction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    bounds: Option<(Point, Point)>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    /// Sets the cell at `point`, returning its previous value.
+    pub fn insert(&mut self, point: Point, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (
+                Point::new(min.x.min(point.x), min.y.min(point.y)),
+                Point::new(max.x.max(point.x), max.y.max(point.y)),
+            ),
+            None => (point, point),
+        });
+        self.cells.insert(point, value)
+    }
+
+    /// Clears the cell at `point`, returning its value.
+    pub fn remove(&mut self, point: Point) -> Option<T> {
+        let value = self.cells.remove(&point)?;
+        let (min, max) = self.bounds.unwrap();
+        if point.x == min.x || point.y == min.y || point.x == max.x || point.y == max.y {
+            self.recompute_bounds();
+        }
+        Some(value)
+    }
+
+    /// Keeps only the cells accepted by `keep`.
+    pub fn retain(&mut self, mut keep: impl FnMut(Point, &T) -> bool) {
+        self.cells.retain(|&point, value| keep(point, value));
+        self.recompute_bounds();
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.cells.get(&point)
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.cells.get_mut(&point)
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.cells.contains_key(&point)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The top left and bottom right corners of the smallest rectangle holding every set cell,
+    /// both inclusive.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        self.bounds
+    }
+
+    /// Every set cell in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(&point, value)| (point, value))
+    }
+
+    /// Draws the bounding box one row per line, showing `empty` for cells that are not set.
+    pub fn render(&self, empty: char) -> String
+    where
+        T: Display,
+    {
+        let Some((min, max)) = self.bounds else {
+            return String::new();
+        };
+        let mut text = String::new();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                match self.get(Point::new(x, y)) {
+                    Some(value) => write!(text, "{}", value).unwrap(),
+                    None => text.push(empty),
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Copies the bounding box into a dense grid, filling unset cells with `empty`. The cell
+    /// at the top left corner of [`SparseGrid::bounds`] ends up at the origin.
+    pub fn to_grid(&self, empty: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let Some((min, max)) = self.bounds else {
+            return Grid::new(vec![], 0, 0);
+        };
+        let (width, height) = ((max.x - min.x + 1) as usize, (max.y - min.y + 1) as usize);
+        let mut grid = Grid::new(vec![empty; width * height], width, height);
+        for (&point, value) in &self.cells {
+            grid[point - min] = value.clone();
+        }
+        grid
+    }
+
+    fn recompute_bounds(&mut self) {
+        self.bounds = self.cells.keys().fold(None, |bounds, &point| {
+            Some(match bounds {
+                Some((min, max)) => (
+                    Point::new(min.x.min(point.x), min.y.min(point.y)),
+                    Point::new(max.x.max(point.x), max.y.max(point.y)),
+                ),
+                None => (point, point),
+            })
+        });
+    }
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        SparseGrid::new()
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(cells: I) -> Self {
+        let mut grid = SparseGrid::new();
+        for (point, value) in cells {
+            grid.insert(point, value);
+        }
+        grid
+    }
+}
+
+/// Every cell of the dense grid, at the same coordinates. Use [`SparseGrid::retain`] to drop
+/// background cells afterwards.
+impl<T: Clone> From<&Grid<T>> for SparseGrid<T> {
+    fn from(grid: &Grid<T>) -> Self {
+        grid.enumerate()
+            .map(|(point, value)| (point, value.clone()))
+            .collect()
+    }
+}
+
+impl<T> Index<Point> for SparseGrid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{} is not set", point))
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let mut grid = SparseGrid::new();
+        grid.insert(Point::new(-2, 1), '#');
+        grid.insert(Point::new(1, -1), '@');
+        grid.insert(Point::new(0, 0), '#');
+        assert_eq!(grid.bounds(), Some((Point::new(-2, -1), Point::new(1, 1))));
+        assert_eq!(grid.render('.'), "...@\n..#.\n#...\n");
+
+        grid.remove(Point::new(-2, 1));
+        assert_eq!(grid.bounds(), Some((Point::new(0, -1), Point::new(1, 0))));
+        assert_eq!(grid[Point::new(1, -1)], '@');
+
+        grid.retain(|_, &c| c != '#');
+        assert_eq!(grid.len(), 1);
+        assert_eq!(grid.bounds(), Some((Point::new(1, -1), Point::new(1, -1))));
+    }
+
+    #[test]
+    fn test_dense_conversion() {
+        let dense = Grid::parse("#..\n.#.\n", Ok::<char, String>).unwrap();
+        let mut sparse = SparseGrid::from(&dense);
+        assert_eq!(sparse.to_grid('.'), dense);
+
+        sparse.retain(|_, &c| c == '#');
+        sparse.insert(Point::new(-1, 2), '#');
+        assert_eq!(sparse.to_grid('.').to_string(), ".#.\n..#\n#..\n");
+        let empty = SparseGrid::<char>::new().to_grid('.');
+        assert_eq!((empty.width(), empty.to_string()), (0, String::new()));
+        assert_eq!(SparseGrid::<char>::new().render('.'), "");
+    }
+}
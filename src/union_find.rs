@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Disjoint sets over the dense indices `0..len`, with path compression and union by rank.
+#[derive(Clone, Debug, Default)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+    sizes: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    /// `len` elements, each in a set of its own.
+    pub fn new(len: usize) -> UnionFind {
+        UnionFind {
+            parents: (0..len).collect(),
+            ranks: vec![0; len],
+            sizes: vec![1; len],
+            components: len,
+        }
+    }
+
+    /// Adds a new element in a set of its own and returns its index.
+    pub fn add(&mut self) -> usize {
+        let index = self.parents.len();
+        self.parents.push(index);
+        self.ranks.push(0);
+        self.sizes.push(1);
+        self.components += 1;
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// The representative of the set containing `element`.
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        // Point everything on the way directly at the root
+        let mut current = element;
+        while self.parents[current] != root {
+            current = std::mem::replace(&mut self.parents[current], root);
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`, returning whether they were separate before.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (child, root) = if self.ranks[a] < self.ranks[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parents[child] = root;
+        self.sizes[root] += self.sizes[child];
+        if self.ranks[child] == self.ranks[root] {
+            self.ranks[root] += 1;
+        }
+        self.components -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The number of elements in the set containing `element`.
+    pub fn size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+
+    /// The number of disjoint sets.
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// Every set, each listing its elements in increasing order. Sets are ordered by their
+    /// smallest element.
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut slots = HashMap::new();
+        let mut components: Vec<Vec<usize>> = vec![];
+        for element in 0..self.len() {
+            let root = self.find(element);
+            let slot = *slots.entry(root).or_insert_with(|| {
+                components.push(vec![]);
+                components.len() - 1
+            });
+            components[slot].push(element);
+        }
+        components
+    }
+}
+
+/// A [`UnionFind`] over arbitrary keys, such as grid points. Keys are added on first use.
+#[derive(Clone, Debug)]
+pub struct KeyedUnionFind<K> {
+    keys: Vec<K>,
+    indices: HashMap<K, usize>,
+    sets: UnionFind,
+}
+
+impl<K: Clone + Eq + Hash> KeyedUnionFind<K> {
+    pub fn new() -> KeyedUnionFind<K> {
+        KeyedUnionFind {
+            keys: vec![],
+            indices: HashMap::new(),
+            sets: UnionFind::new(0),
+        }
+    }
+
+    /// Adds `key` in a set of its own, unless it is already known.
+    pub fn insert(&mut self, key: K) {
+        self.index_of(key);
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The representative key of the set containing `key`, if `key` is known.
+    pub fn find(&mut self, key: &K) -> Option<&K> {
+        let index = *self.indices.get(key)?;
+        Some(&self.keys[self.sets.find(index)])
+    }
+
+    /// Merges the sets containing `a` and `b`, returning whether they were separate before.
+    pub fn union(&mut self, a: K, b: K) -> bool {
+        let (a, b) = (self.index_of(a), self.index_of(b));
+        self.sets.union(a, b)
+    }
+
+    pub fn connected(&mut self, a: &K, b: &K) -> bool {
+        match (self.indices.get(a), self.indices.get(b)) {
+            (Some(&a), Some(&b)) => self.sets.connected(a, b),
+            _ => a == b,
+        }
+    }
+
+    /// The number of keys in the set containing `key`, or zero if `key` is unknown.
+    pub fn size(&mut self, key: &K) -> usize {
+        match self.indices.get(key) {
+            Some(&index) => self.sets.size(index),
+            None => 0,
+        }
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.sets.component_count()
+    }
+
+    /// Every set, each listing its keys in insertion order.
+    pub fn components(&mut self) -> Vec<Vec<K>> {
+        self.sets
+            .components()
+            .into_iter()
+            .map(|set| {
+                set.into_iter()
+                    .map(|index| self.keys[index].clone())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn index_of(&mut self, key: K) -> usize {
+        if let Some(&index) = self.indices.get(&key) {
+            return index;
+        }
+        let index = self.sets.add();
+        self.keys.push(key.clone());
+        self.indices.insert(key, index);
+        index
+    }
+}
+
+impl<K: Clone + Eq + Hash> Default for KeyedUnionFind<K> {
+    fn default() -> Self {
+        KeyedUnionFind::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use super::*;
+    use crate::point::Point;
+
+    #[test]
+    fn test_dense() {
+        let mut sets = UnionFind::new(6);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(sets.union(1, 4));
+        assert!(!sets.union(0, 3));
+
+        assert!(sets.connected(0, 4));
+        assert!(!sets.connected(2, 5));
+        assert_eq!((sets.size(3), sets.size(5)), (4, 1));
+        assert_eq!(sets.component_count(), 3);
+        assert_eq!(sets.components(), vec![vec![0, 1, 3, 4], vec![2], vec![5]]);
+
+        let added = sets.add();
+        assert_eq!((added, sets.component_count()), (6, 4));
+    }
+
+    #[test]
+    fn test_keyed() {
+        let mut sets = KeyedUnionFind::new();
+        sets.union(Point::new(0, 0), Point::new(1, 0));
+        sets.union(Point::new(5, 5), Point::new(5, 6));
+        sets.insert(Point::new(9, 9));
+        sets.union(Point::new(1, 0), Point::new(1, 1));
+
+        assert_eq!(sets.len(), 6);
+        assert_eq!(sets.component_count(), 3);
+        assert_eq!(sets.size(&Point::new(1, 1)), 3);
+        assert_eq!(sets.size(&Point::new(7, 7)), 0);
+        assert!(sets.connected(&Point::new(0, 0), &Point::new(1, 1)));
+        assert!(!sets.connected(&Point::new(0, 0), &Point::new(5, 5)));
+        assert_eq!(sets.find(&Point::new(9, 9)), Some(&Point::new(9, 9)));
+        assert_eq!(
+            sets.components()[1],
+            vec![Point::new(5, 5), Point::new(5, 6)]
+        );
+    }
+}